@@ -2,6 +2,9 @@ use handlebars::{
     Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderErrorReason,
 };
 
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::HelperRegistry;
+
 fn assign_fct(
     h: &Helper,
     _: &Handlebars,
@@ -44,7 +47,17 @@ fn set_fct(
     Ok(())
 }
 
-pub fn register(handlebars: &mut Handlebars) {
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "assign", feature: Some("jsontype");
+    "assign"(name: "string", value: "any") -> "none" {
+        r##"{{ assign "foo" "hello world" }}{{ foo }}"## => "hello world"
+    }
+    "set"() [name: "any*"] -> "none" {
+        r##"{{ set foo="world" bar="hello" }}{{ bar }} {{ foo }}"## => "hello world"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
     handlebars.register_helper("assign", Box::new(assign_fct));
     handlebars.register_helper("set", Box::new(set_fct));
 }
@@ -64,10 +77,6 @@ mod tests {
                 r##"{{ assign "foo" {"bar": 33} }}{{ foo }}"##,
                 r##"[object]"##,
             ),
-            (
-                r##"{{ assign "foo" "hello world" }}{{ foo }}"##,
-                r##"hello world"##,
-            ),
             (
                 r##"{{ assign "foo" "world" }}{{ assign "bar" "hello" }}>{{ bar }} {{ foo }}<"##,
                 r##">hello world<"##,
@@ -0,0 +1,297 @@
+//! Metadata of the helpers provided by the crate (name, group, parameters, example,...),
+//! eg to list the available helpers in a CLI or to generate their documentation.
+//!
+//! ```rust
+//! let info = handlebars_misc_helpers::catalog()
+//!     .into_iter()
+//!     .find(|info| info.name == "file_name")
+//!     .expect("file_name in the catalog");
+//! assert_eq!(info.group, "path");
+//! assert_eq!(info.params[0].ty, "string");
+//!
+//! let handlebars = handlebars_misc_helpers::new_hbs();
+//! let data = std::collections::HashMap::<String, String>::new();
+//! assert_eq!(handlebars.render_template(info.example, &data)?, info.expected);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::Capability;
+
+/// A parameter (positional or hash) of a helper
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    /// `string`, `integer`, `number`, `boolean`, `array`, `object`, `any` (or several separated by `|`),
+    /// followed by `?` if the positional parameter is optional, `*` for zero or more values,
+    /// `+` for one or more values (the hash parameters are always optional, a hash parameter
+    /// with `*` accepts any name, eg `set`)
+    pub ty: &'static str,
+    /// The value used if the parameter is not provided (if any)
+    pub default: Option<&'static str>,
+}
+
+/// Description of a helper, see [`catalog`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HelperInfo {
+    /// Name of the helper in the templates (without the prefix of [`crate::HelpersConfig`])
+    pub name: &'static str,
+    /// Group of the helper (its module), as selected by [`crate::HelpersConfig`]
+    pub group: &'static str,
+    /// Cargo feature required by the helper (`None` if always available)
+    pub feature: Option<&'static str>,
+    /// Capability required by the helper, see [`crate::Capabilities`]
+    pub capability: Option<Capability>,
+    pub params: &'static [Param],
+    pub hash: &'static [Param],
+    /// Type of the returned value (same notation as [`Param::ty`]), `block` for a block helper,
+    /// `none` for a helper that renders nothing
+    pub returns: &'static str,
+    /// A template using the helper
+    pub example: &'static str,
+    /// The rendering of `example` by a registry configured with [`crate::setup_handlebars`]
+    /// (with an empty object as data, and `\n` as line ending). The examples are rendered and
+    /// compared to `expected` by the tests of the crate, except the ones of the helpers requiring
+    /// the network: those are never rendered, so their `expected` is only indicative
+    pub expected: &'static str,
+    /// `example` reads the files of this crate (eg `Cargo.toml`), so `expected` is its rendering
+    /// with the root of the repository of the crate as current directory
+    pub example_in_repo: bool,
+}
+
+/// Declare the catalog of a module:
+///
+/// ```text
+/// helpers_info! {
+///     group: "string", feature: Some("string");
+///     "pad_start"(s: "string", width: "integer") [pad: "string" = " "] -> "string" {
+///         r#"{{ pad_start "42" 5 pad="0" }}"# => "00042"
+///     }
+///     "http_get"(url: "string") -> "string", requires Network {
+///         r#"{{ http_get "https://example.com" }}"# => "..."
+///     }
+///     "file_sha256"(path: "string") -> "string", requires FsRead {
+///         r#"{{ len (file_sha256 "Cargo.toml") }}"# => "64", in_repo
+///     }
+/// }
+/// ```
+macro_rules! helpers_info {
+    (@default) => {
+        None
+    };
+    (@default $default:literal) => {
+        Some($default)
+    };
+    (@capability) => {
+        None
+    };
+    (@capability $capability:ident) => {
+        Some($crate::Capability::$capability)
+    };
+    (@in_repo) => {
+        false
+    };
+    (@in_repo in_repo) => {
+        true
+    };
+    (
+        group: $group:literal, feature: $feature:expr;
+        $(
+            $name:literal ($($param:ident: $ty:literal $(= $default:literal)?),* $(,)?)
+            $([$($hash:ident: $hash_ty:literal $(= $hash_default:literal)?),* $(,)?])?
+            -> $returns:literal $(, requires $capability:ident)?
+            { $example:literal => $expected:literal $(, $in_repo:ident)? }
+        )*
+    ) => {
+        &[$(
+            $crate::catalog::HelperInfo {
+                name: $name,
+                group: $group,
+                feature: $feature,
+                capability: $crate::catalog::helpers_info!(@capability $($capability)?),
+                params: &[$(
+                    $crate::catalog::Param {
+                        name: stringify!($param),
+                        ty: $ty,
+                        default: $crate::catalog::helpers_info!(@default $($default)?),
+                    }
+                ),*],
+                hash: &[$($(
+                    $crate::catalog::Param {
+                        name: stringify!($hash),
+                        ty: $hash_ty,
+                        default: $crate::catalog::helpers_info!(@default $($hash_default)?),
+                    }
+                ),*)?],
+                returns: $returns,
+                example: $example,
+                expected: $expected,
+                example_in_repo: $crate::catalog::helpers_info!(@in_repo $($in_repo)?),
+            }
+        ),*]
+    };
+}
+pub(crate) use helpers_info;
+
+/// The helpers available with the enabled cargo features (registered by [`crate::register`])
+pub fn catalog() -> Vec<&'static HelperInfo> {
+    let mut catalog: Vec<&'static HelperInfo> = vec![];
+    #[cfg(feature = "string")]
+    catalog.extend(crate::string_helpers::CATALOG);
+    #[cfg(feature = "string")]
+    catalog.extend(crate::escape_helpers::CATALOG);
+    #[cfg(any(feature = "http_reqwest", feature = "http_attohttpc"))]
+    catalog.extend(crate::http_helpers::CATALOG);
+    catalog.extend(crate::path_helpers::CATALOG);
+    catalog.extend(crate::env_helpers::CATALOG);
+    #[cfg(feature = "json")]
+    catalog.extend(crate::json_helpers::CATALOG);
+    #[cfg(feature = "jsonnet")]
+    catalog.extend(crate::jsonnet_helpers::CATALOG);
+    #[cfg(feature = "jsontype")]
+    catalog.extend(crate::assign_helpers::CATALOG);
+    catalog.extend(crate::file_helpers::CATALOG);
+    #[cfg(feature = "encoding")]
+    catalog.extend(crate::encoding_helpers::CATALOG);
+    #[cfg(feature = "hash")]
+    catalog.extend(crate::hash_helpers::CATALOG);
+    #[cfg(feature = "jsontype")]
+    catalog.extend(crate::region_helpers::CATALOG);
+    #[cfg(feature = "regex")]
+    catalog.extend(crate::regex_helpers::CATALOG);
+    #[cfg(feature = "uuid")]
+    catalog.extend(crate::uuid_helpers::CATALOG);
+    #[cfg(feature = "datetime")]
+    catalog.extend(crate::datetime_helpers::CATALOG);
+    #[cfg(feature = "math")]
+    catalog.extend(crate::math_helpers::CATALOG);
+    #[cfg(feature = "logic")]
+    catalog.extend(crate::logic_helpers::CATALOG);
+    #[cfg(feature = "collection")]
+    catalog.extend(crate::collection_helpers::CATALOG);
+    catalog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HelperRegistry, HelpersConfig};
+    use handlebars::{HelperDef, RenderErrorReason};
+    use pretty_assertions::assert_eq;
+    use std::collections::{BTreeSet, HashMap};
+    use std::error::Error;
+
+    /// All the helpers are available (an example could use the helpers of another group)
+    const ALL_FEATURES: bool = cfg!(all(
+        feature = "collection",
+        feature = "datetime",
+        feature = "encoding",
+        feature = "hash",
+        feature = "json",
+        feature = "jsonnet",
+        feature = "logic",
+        feature = "math",
+        feature = "regex",
+        feature = "string",
+        feature = "uuid",
+    ));
+
+    /// Render the example of every helper (except the ones requiring the network), the tests
+    /// are run from the root of the crate (for the examples `in_repo`)
+    #[test]
+    fn test_catalog_examples() -> Result<(), Box<dyn Error>> {
+        let hbs = crate::new_hbs();
+        let data: HashMap<String, String> = HashMap::new();
+        for info in catalog() {
+            assert!(
+                info.example.contains(info.name),
+                "example of {}: {}",
+                info.name,
+                info.example
+            );
+            if info.capability == Some(Capability::Network) {
+                continue;
+            }
+            let rendered = match hbs.render_template(info.example, &data) {
+                Err(e)
+                    if !ALL_FEATURES
+                        && matches!(e.reason(), RenderErrorReason::HelperNotFound(_)) =>
+                {
+                    continue;
+                }
+                rendered => rendered?,
+            };
+            assert_eq!(
+                rendered.replace("\r\n", "\n"),
+                info.expected,
+                "example of {}: {}",
+                info.name,
+                info.example
+            );
+        }
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct NamesRegistry(BTreeSet<String>);
+
+    impl HelperRegistry for NamesRegistry {
+        fn register_helper(&mut self, name: &str, _: Box<dyn HelperDef + Send + Sync + 'static>) {
+            self.0.insert(name.to_owned());
+        }
+    }
+
+    #[test]
+    fn test_catalog_lists_the_registered_helpers() {
+        let mut registered = NamesRegistry::default();
+        HelpersConfig::new().register(&mut registered);
+        let catalog = catalog();
+        let names = catalog.iter().map(|info| info.name.to_owned()).collect();
+        assert_eq!(registered.0, names);
+        assert_eq!(catalog.len(), names.len(), "duplicated names");
+        for info in catalog {
+            let mut group = NamesRegistry::default();
+            HelpersConfig::new()
+                .include([info.group])
+                .register(&mut group);
+            assert!(
+                group.0.contains(info.name),
+                "{} in {}",
+                info.name,
+                info.group
+            );
+        }
+    }
+
+    /// The capabilities required by the registered helpers
+    #[derive(Default)]
+    struct CapabilitiesRegistry(HashMap<String, Option<Capability>>);
+
+    impl HelperRegistry for CapabilitiesRegistry {
+        fn register_helper(&mut self, name: &str, _: Box<dyn HelperDef + Send + Sync + 'static>) {
+            self.0.insert(name.to_owned(), None);
+        }
+
+        fn register_helper_requiring(
+            &mut self,
+            capability: Capability,
+            name: &str,
+            _: Box<dyn HelperDef + Send + Sync + 'static>,
+        ) {
+            self.0.insert(name.to_owned(), Some(capability));
+        }
+    }
+
+    #[test]
+    fn test_capabilities_from_catalog() {
+        let mut registered = CapabilitiesRegistry::default();
+        HelpersConfig::new().register(&mut registered);
+        for info in catalog() {
+            assert_eq!(
+                registered.0.get(info.name),
+                Some(&info.capability),
+                "{}",
+                info.name
+            );
+        }
+    }
+}
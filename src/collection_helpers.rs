@@ -0,0 +1,551 @@
+use handlebars::{handlebars_helper, RenderError};
+use serde_json::{Map, Value as Json};
+use std::cmp::Ordering;
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::logic_helpers::to_plain_string;
+use crate::HelperRegistry;
+
+/// Max number of items generated by `range`, to protect against typo like `range 1000000000`
+const RANGE_MAX_LEN: i64 = 1_000_000;
+
+/// Order json values: null < bool < number < string < array < object,
+/// values of the same type are compared by value (arrays and objects are considered equal)
+pub(crate) fn compare_json(a: &Json, b: &Json) -> Ordering {
+    fn rank(v: &Json) -> u8 {
+        match v {
+            Json::Null => 0,
+            Json::Bool(_) => 1,
+            Json::Number(_) => 2,
+            Json::String(_) => 3,
+            Json::Array(_) => 4,
+            Json::Object(_) => 5,
+        }
+    }
+    match (a, b) {
+        (Json::Bool(a), Json::Bool(b)) => a.cmp(b),
+        (Json::Number(a), Json::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Json::String(a), Json::String(b)) => a.cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Get the value at a dot separated `path` (eg `"author.name"`, `"tags.0"`)
+fn get_path<'a>(v: &'a Json, path: &str) -> Option<&'a Json> {
+    path.split('.')
+        .filter(|s| !s.is_empty())
+        .try_fold(v, |current, key| match current {
+            Json::Object(m) => m.get(key),
+            Json::Array(a) => key.parse::<usize>().ok().and_then(|i| a.get(i)),
+            _ => None,
+        })
+}
+
+/// Convert a possibly negative index (from the end) into an index in `[0, len]`
+fn to_index(i: i64, len: usize) -> usize {
+    if i < 0 {
+        len.saturating_sub(i.unsigned_abs() as usize)
+    } else {
+        (i as usize).min(len)
+    }
+}
+
+fn find_i64(helper: &str, v: &Json) -> Result<i64, RenderError> {
+    v.as_i64()
+        .or_else(|| v.as_str().and_then(|s| s.trim().parse::<i64>().ok()))
+        .ok_or_else(|| crate::to_other_error(format!("{}: '{}' is not an integer", helper, v)))
+}
+
+/// Collect the keys from the params (strings or arrays of strings)
+fn find_keys(args: &[&Json]) -> Vec<String> {
+    args.iter()
+        .flat_map(|v| match v {
+            Json::Array(items) => items.iter().map(to_plain_string).collect::<Vec<_>>(),
+            _ => vec![to_plain_string(v)],
+        })
+        .collect()
+}
+
+fn concat(args: &[&Json]) -> Json {
+    let mut result = vec![];
+    for v in args {
+        match v {
+            Json::Array(items) => result.extend(items.iter().cloned()),
+            _ => result.push((*v).clone()),
+        }
+    }
+    Json::Array(result)
+}
+
+fn slice(v: &[Json], start: i64, end: Option<i64>) -> Json {
+    let start = to_index(start, v.len());
+    let end = end.map(|e| to_index(e, v.len())).unwrap_or(v.len());
+    if start >= end {
+        Json::Array(vec![])
+    } else {
+        Json::Array(v[start..end].to_vec())
+    }
+}
+
+fn reverse(v: &Json) -> Result<Json, RenderError> {
+    match v {
+        Json::Array(items) => Ok(Json::Array(items.iter().rev().cloned().collect())),
+        Json::String(s) => Ok(Json::String(s.chars().rev().collect())),
+        _ => Err(crate::to_other_error(format!(
+            "reverse: '{}' is not an array or a string",
+            v
+        ))),
+    }
+}
+
+fn sort_by(v: &[Json], key: Option<&str>) -> Json {
+    let mut items = v.to_vec();
+    match key {
+        Some(key) => items.sort_by(|a, b| {
+            compare_json(
+                get_path(a, key).unwrap_or(&Json::Null),
+                get_path(b, key).unwrap_or(&Json::Null),
+            )
+        }),
+        None => items.sort_by(compare_json),
+    }
+    Json::Array(items)
+}
+
+fn unique(v: &[Json]) -> Json {
+    let mut result: Vec<Json> = Vec::with_capacity(v.len());
+    for item in v {
+        if !result.contains(item) {
+            result.push(item.clone());
+        }
+    }
+    Json::Array(result)
+}
+
+fn group_by(v: &[Json], key: &str) -> Json {
+    let mut groups = Map::new();
+    for item in v {
+        let group = to_plain_string(get_path(item, key).unwrap_or(&Json::Null));
+        match groups.entry(group).or_insert_with(|| Json::Array(vec![])) {
+            Json::Array(items) => items.push(item.clone()),
+            _ => unreachable!("groups only contains arrays"),
+        }
+    }
+    Json::Object(groups)
+}
+
+fn entries(v: &Map<String, Json>) -> Json {
+    Json::Array(
+        v.iter()
+            .map(|(k, v)| {
+                let mut entry = Map::new();
+                entry.insert("key".to_owned(), Json::String(k.to_owned()));
+                entry.insert("value".to_owned(), v.clone());
+                Json::Object(entry)
+            })
+            .collect(),
+    )
+}
+
+fn merge_into(target: &mut Map<String, Json>, source: &Map<String, Json>, deep: bool) {
+    for (k, v) in source {
+        match (target.get_mut(k), v) {
+            (Some(Json::Object(t)), Json::Object(s)) if deep => merge_into(t, s, deep),
+            _ => {
+                target.insert(k.to_owned(), v.clone());
+            }
+        }
+    }
+}
+
+fn merge(args: &[&Json], deep: bool) -> Result<Json, RenderError> {
+    let mut result = Map::new();
+    for v in args {
+        match v {
+            Json::Object(m) => merge_into(&mut result, m, deep),
+            Json::Null => {}
+            _ => {
+                return Err(crate::to_other_error(format!(
+                    "merge: '{}' is not an object",
+                    v
+                )))
+            }
+        }
+    }
+    Ok(Json::Object(result))
+}
+
+fn pick(v: &Map<String, Json>, keys: &[String], keep: bool) -> Json {
+    Json::Object(
+        v.iter()
+            .filter(|(k, _)| keys.contains(k) == keep)
+            .map(|(k, v)| (k.to_owned(), v.clone()))
+            .collect(),
+    )
+}
+
+fn join(v: &[Json], sep: &str) -> String {
+    v.iter().map(to_plain_string).collect::<Vec<_>>().join(sep)
+}
+
+/// Split `s` by `sep` into at most `limit` items (no limit if 0)
+fn split(s: &str, sep: &str, limit: usize) -> Json {
+    let items: Box<dyn Iterator<Item = &str>> = if limit > 0 {
+        Box::new(s.splitn(limit, sep))
+    } else {
+        Box::new(s.split(sep))
+    };
+    Json::Array(items.map(|item| Json::String(item.to_owned())).collect())
+}
+
+fn range(args: &[&Json]) -> Result<Json, RenderError> {
+    let args = args
+        .iter()
+        .map(|v| find_i64("range", v))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (start, end, step) = match args[..] {
+        [end] => (0, end, 1),
+        [start, end] => (start, end, 1),
+        [start, end, step] => (start, end, step),
+        _ => {
+            return Err(crate::to_other_error(
+                "range: expected 1 to 3 integers (end | start end | start end step)",
+            ))
+        }
+    };
+    if step == 0 {
+        return Err(crate::to_other_error("range: step can not be 0"));
+    }
+    // computed with i128 to not overflow on the bounds of i64 (eg `i64::MIN / -1`)
+    let (span, step_len) = (i128::from(end) - i128::from(start), i128::from(step));
+    let len = if span.signum() == step_len.signum() {
+        (span.abs() + step_len.abs() - 1) / step_len.abs()
+    } else {
+        0
+    };
+    if len > i128::from(RANGE_MAX_LEN) {
+        return Err(crate::to_other_error(format!(
+            "range: too many items ({} > {})",
+            len, RANGE_MAX_LEN
+        )));
+    }
+    let mut result = vec![];
+    let mut current = Some(start);
+    while let Some(value) = current.filter(|v| (step > 0 && *v < end) || (step < 0 && *v > end)) {
+        result.push(Json::from(value));
+        current = value.checked_add(step);
+    }
+    Ok(Json::Array(result))
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "collection", feature: Some("collection");
+    "array"(values: "any*") -> "array" {
+        r##"{{ json_to_str (array 1 "a" true) }}"## => r##"[1,"a",true]"##
+    }
+    "concat"(values: "any*") -> "array" {
+        r##"{{ json_to_str (concat [1, 2] [3] 4) }}"## => "[1,2,3,4]"
+    }
+    "first"(a: "array") -> "any" {
+        r##"{{ first [1, 2, 3] }}"## => "1"
+    }
+    "last"(a: "array") -> "any" {
+        r##"{{ last [1, 2, 3] }}"## => "3"
+    }
+    "slice"(a: "array", start: "integer", end: "integer?") -> "array" {
+        r##"{{ json_to_str (slice [1, 2, 3, 4] 1 3) }}"## => "[2,3]"
+    }
+    "reverse"(v: "array|string") -> "array|string" {
+        r##"{{ json_to_str (reverse [1, 2, 3]) }}"## => "[3,2,1]"
+    }
+    "sort_by"(a: "array", key: "string?") -> "array" {
+        r##"{{ json_to_str (sort_by [{"v": 2}, {"v": 1}] "v") }}"## => r##"[{"v":1},{"v":2}]"##
+    }
+    "unique"(a: "array") -> "array" {
+        r##"{{ json_to_str (unique [1, 2, 1, 3]) }}"## => "[1,2,3]"
+    }
+    "group_by"(a: "array", key: "string") -> "object" {
+        r##"{{ json_to_str (group_by [{"k": "a"}, {"k": "b"}] "k") }}"## => r##"{"a":[{"k":"a"}],"b":[{"k":"b"}]}"##
+    }
+    "keys"(o: "object") -> "array" {
+        r##"{{ json_to_str (keys {"a": 1, "b": 2}) }}"## => r##"["a","b"]"##
+    }
+    "values"(o: "object") -> "array" {
+        r##"{{ json_to_str (values {"a": 1, "b": 2}) }}"## => "[1,2]"
+    }
+    "entries"(o: "object") -> "array" {
+        r##"{{ json_to_str (entries {"a": 1}) }}"## => r##"[{"key":"a","value":1}]"##
+    }
+    "merge"(objects: "object*") [deep: "boolean" = "false"] -> "object" {
+        r##"{{ json_to_str (merge {"o": {"x": 1}} {"o": {"y": 2}} deep=true) }}"## => r##"{"o":{"x":1,"y":2}}"##
+    }
+    "pick"(o: "object", keys: "string*|array") -> "object" {
+        r##"{{ json_to_str (pick {"a": 1, "b": 2, "c": 3} "a" "c") }}"## => r##"{"a":1,"c":3}"##
+    }
+    "omit"(o: "object", keys: "string*|array") -> "object" {
+        r##"{{ json_to_str (omit {"a": 1, "b": 2, "c": 3} "a" "c") }}"## => r##"{"b":2}"##
+    }
+    "join"(a: "array", sep: "string") -> "string" {
+        r##"{{ join ["a", "b", "c"] ", " }}"## => "a, b, c"
+    }
+    "split"(s: "string", sep: "string") [limit: "integer" = "0"] -> "array" {
+        r##"{{ json_to_str (split "a,b,c" "," limit=2) }}"## => r##"["a","b,c"]"##
+    }
+    "range"(start: "integer?", end: "integer", step: "integer?") -> "array" {
+        r##"{{ json_to_str (range 0 10 3) }}"## => "[0,3,6,9]"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    {
+        handlebars_helper!(array: |*args| Json::Array(args.into_iter().cloned().collect()));
+        handlebars.register_helper("array", Box::new(array))
+    }
+    {
+        handlebars_helper!(concat_fct: |*args| concat(&args));
+        handlebars.register_helper("concat", Box::new(concat_fct))
+    }
+    {
+        handlebars_helper!(first: |v: array| v.first().cloned().unwrap_or(Json::Null));
+        handlebars.register_helper("first", Box::new(first))
+    }
+    {
+        handlebars_helper!(last: |v: array| v.last().cloned().unwrap_or(Json::Null));
+        handlebars.register_helper("last", Box::new(last))
+    }
+    {
+        handlebars_helper!(slice_fct: |v: array, start: i64, *args| {
+            let end = args.get(2).map(|e| find_i64("slice", e)).transpose()?;
+            slice(v, start, end)
+        });
+        handlebars.register_helper("slice", Box::new(slice_fct))
+    }
+    {
+        handlebars_helper!(reverse_fct: |v: Json| reverse(v)?);
+        handlebars.register_helper("reverse", Box::new(reverse_fct))
+    }
+    {
+        handlebars_helper!(sort_by_fct: |v: array, *args| sort_by(v, args.get(1).and_then(|k| k.as_str())));
+        handlebars.register_helper("sort_by", Box::new(sort_by_fct))
+    }
+    {
+        handlebars_helper!(unique_fct: |v: array| unique(v));
+        handlebars.register_helper("unique", Box::new(unique_fct))
+    }
+    {
+        handlebars_helper!(group_by_fct: |v: array, key: str| group_by(v, key));
+        handlebars.register_helper("group_by", Box::new(group_by_fct))
+    }
+    {
+        handlebars_helper!(keys: |v: object| v.keys().map(|k| Json::String(k.to_owned())).collect::<Vec<_>>());
+        handlebars.register_helper("keys", Box::new(keys))
+    }
+    {
+        handlebars_helper!(values: |v: object| v.values().cloned().collect::<Vec<_>>());
+        handlebars.register_helper("values", Box::new(values))
+    }
+    {
+        handlebars_helper!(entries_fct: |v: object| entries(v));
+        handlebars.register_helper("entries", Box::new(entries_fct))
+    }
+    {
+        handlebars_helper!(merge_fct: |{deep: bool = false}, *args| merge(&args, deep)?);
+        handlebars.register_helper("merge", Box::new(merge_fct))
+    }
+    {
+        handlebars_helper!(pick_fct: |v: object, *args| pick(v, &find_keys(&args[1..]), true));
+        handlebars.register_helper("pick", Box::new(pick_fct))
+    }
+    {
+        handlebars_helper!(omit_fct: |v: object, *args| pick(v, &find_keys(&args[1..]), false));
+        handlebars.register_helper("omit", Box::new(omit_fct))
+    }
+    {
+        handlebars_helper!(join_fct: |v: array, sep: str| join(v, sep));
+        handlebars.register_helper("join", Box::new(join_fct))
+    }
+    {
+        handlebars_helper!(split_fct: |s: str, sep: str, {limit: u64 = 0}| split(s, sep, limit as usize));
+        handlebars.register_helper("split", Box::new(split_fct))
+    }
+    {
+        handlebars_helper!(range_fct: |*args| range(&args)?);
+        handlebars.register_helper("range", Box::new(range_fct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "json")]
+    use crate::assert_renders;
+    #[cfg(feature = "json")]
+    use std::error::Error;
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_array_concat() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ json_to_str (array) }}"##, r##"[]"##),
+            (
+                r##"{{#each (concat (array "a" "b") "c") }}{{ this }}{{/each}}"##,
+                r##"abc"##
+            ),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_first_last_slice_reverse() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ first [] }}"##, r##""##),
+            (
+                r##"{{ json_to_str (slice [1, 2, 3, 4] 1) }}"##,
+                r##"[2,3,4]"##
+            ),
+            (
+                r##"{{ json_to_str (slice [1, 2, 3, 4] -2) }}"##,
+                r##"[3,4]"##
+            ),
+            (r##"{{ json_to_str (slice [1, 2, 3, 4] 3 1) }}"##, r##"[]"##),
+            (r##"{{ reverse "abc" }}"##, r##"cba"##),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_sort_by_unique_group_by() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ json_to_str (sort_by [3, 1, 2]) }}"##, r##"[1,2,3]"##),
+            (
+                r##"{{ json_to_str (sort_by ["b", "a", "c"]) }}"##,
+                r##"["a","b","c"]"##
+            ),
+            (
+                r##"{{ json_to_str (sort_by [{"n": "b", "v": 2}, {"n": "a", "v": 3}, {"n": "c", "v": 1}] "v") }}"##,
+                r##"[{"n":"c","v":1},{"n":"b","v":2},{"n":"a","v":3}]"##
+            ),
+            (
+                r##"{{#each (sort_by [{"p": {"n": "b"}}, {"p": {"n": "a"}}] "p.n") }}{{ this.p.n }}{{/each}}"##,
+                r##"ab"##
+            ),
+            (
+                r##"{{ json_to_str (unique [1, 2, 1, "1", 3, 2]) }}"##,
+                r##"[1,2,"1",3]"##
+            ),
+            (
+                r##"{{ json_to_str (group_by [{"k": "a", "v": 1}, {"k": "b", "v": 2}, {"k": "a", "v": 3}] "k") }}"##,
+                r##"{"a":[{"k":"a","v":1},{"k":"a","v":3}],"b":[{"k":"b","v":2}]}"##
+            ),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_keys_values_entries() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ json_to_str (entries {"a": 1, "b": 2}) }}"##,
+                r##"[{"key":"a","value":1},{"key":"b","value":2}]"##
+            ),
+            (
+                r##"{{#each (entries {"a": 1, "b": 2}) }}{{ this.key }}={{ this.value }};{{/each}}"##,
+                r##"a=1;b=2;"##
+            ),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_merge_pick_omit() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ json_to_str (merge {"a": 1, "o": {"x": 1}} {"b": 2, "o": {"y": 2}}) }}"##,
+                r##"{"a":1,"b":2,"o":{"y":2}}"##
+            ),
+            (
+                r##"{{ json_to_str (merge {"a": 1, "o": {"x": 1}} {"b": 2, "o": {"y": 2}} deep=true) }}"##,
+                r##"{"a":1,"b":2,"o":{"x":1,"y":2}}"##
+            ),
+            (
+                r##"{{ json_to_str (merge {"a": 1} null {"a": 2}) }}"##,
+                r##"{"a":2}"##
+            ),
+            (
+                r##"{{ json_to_str (pick {"a": 1, "b": 2, "c": 3} ["a", "b"]) }}"##,
+                r##"{"a":1,"b":2}"##
+            ),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_join_split() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ join [1, true, null, "x"] "-" }}"##, r##"1-true--x"##),
+            (r##"{{ join [] "-" }}"##, r##""##),
+            (
+                r##"{{ json_to_str (split "a,b,,c" ",") }}"##,
+                r##"["a","b","","c"]"##
+            ),
+            (r##"{{ join (split "a,b,c" ",") "/" }}"##, r##"a/b/c"##),
+            (
+                r##"{{ json_to_str (split "a,b,c" "," limit=5) }}"##,
+                r##"["a","b","c"]"##
+            ),
+            (
+                r##"{{#each (split "x y" " ") }}<{{ this }}>{{/each}}"##,
+                r##"<x><y>"##
+            ),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_range() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ json_to_str (range 3) }}"##, r##"[0,1,2]"##),
+            (r##"{{ json_to_str (range 1 4) }}"##, r##"[1,2,3]"##),
+            (r##"{{ json_to_str (range 3 0 -1) }}"##, r##"[3,2,1]"##),
+            (r##"{{ json_to_str (range 0) }}"##, r##"[]"##),
+            (
+                r##"{{#each (range 1 4) }}{{ this }};{{/each}}"##,
+                r##"1;2;3;"##
+            ),
+            (r##"{{ json_to_str (range 1 4 -1) }}"##, r##"[]"##),
+            (r##"{{ json_to_str (range 0 10 4) }}"##, r##"[0,4,8]"##),
+            (
+                r##"{{ json_to_str (range 9223372036854775805 9223372036854775807 5) }}"##,
+                r##"[9223372036854775805]"##
+            ),
+            (
+                r##"{{ json_to_str (range 9223372036854775805 9223372036854775807) }}"##,
+                r##"[9223372036854775805,9223372036854775806]"##
+            ),
+            (
+                r##"{{ json_to_str (range -9223372036854775807 -9223372036854775808 -2) }}"##,
+                r##"[-9223372036854775807]"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let hbs = crate::new_hbs();
+        let data = serde_json::json!({});
+        for tmpl in [
+            r##"{{ range 0 10 0 }}"##,
+            r##"{{ range "a" }}"##,
+            r##"{{ range 0 100000000 }}"##,
+            r##"{{ range 9223372036854775807 -9223372036854775808 -1 }}"##,
+            r##"{{ range -9223372036854775808 9223372036854775807 }}"##,
+            r##"{{ merge {"a": 1} [1] }}"##,
+            r##"{{ reverse 1 }}"##,
+            r##"{{ keys [1] }}"##,
+        ] {
+            assert!(hbs.render_template(tmpl, &data).is_err(), "{}", tmpl);
+        }
+    }
+}
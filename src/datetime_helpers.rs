@@ -0,0 +1,522 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeDelta, TimeZone, Utc,
+};
+use handlebars::{
+    handlebars_helper, Context, Handlebars, Helper, HelperDef, RenderContext, RenderError,
+    ScopedJson,
+};
+use serde_json::Value as Json;
+use std::sync::Arc;
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::env_helpers::{Environment, ProcessEnvironment};
+use crate::HelperRegistry;
+
+/// Source of "now" for the `now` helper.
+///
+/// Provide your own implementation (or a [`FixedClock`]) via [`register_with_clock`]
+/// to pin the time in tests or reproducible builds.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The clock of the system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock always returning the same time
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+fn clock_from_source_date_epoch(v: &str) -> Option<FixedClock> {
+    v.trim()
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map(FixedClock)
+}
+
+/// Return a clock pinned to `SOURCE_DATE_EPOCH` (see [reproducible-builds.org](https://reproducible-builds.org/specs/source-date-epoch/))
+/// if the environment variable is defined, else the [`SystemClock`].
+pub fn clock_from_env() -> Arc<dyn Clock> {
+    clock_from_environment(&ProcessEnvironment)
+}
+
+/// Same as [`clock_from_env`], with `SOURCE_DATE_EPOCH` read from `env`
+pub fn clock_from_environment(env: &dyn Environment) -> Arc<dyn Clock> {
+    match env.var("SOURCE_DATE_EPOCH") {
+        Some(v) => match clock_from_source_date_epoch(&v) {
+            Some(clock) => Arc::new(clock),
+            None => {
+                log::warn!(
+                    "SOURCE_DATE_EPOCH '{:?}' is not a valid unix timestamp, use the system clock",
+                    v
+                );
+                Arc::new(SystemClock)
+            }
+        },
+        None => Arc::new(SystemClock),
+    }
+}
+
+/// Parse a date from:
+/// - a number of seconds since the unix epoch
+/// - a RFC 3339 string (`"2024-03-01T10:20:30Z"`, `"2024-03-01T10:20:30+02:00"`)
+/// - a string without offset (`"2024-03-01T10:20:30"`, `"2024-03-01 10:20:30"`, `"2024-03-01"`), considered as UTC
+fn parse_date(v: &Json) -> Result<DateTime<FixedOffset>, RenderError> {
+    let date = match v {
+        Json::Number(n) => n
+            .as_i64()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .or_else(|| {
+                n.as_f64().and_then(|secs| {
+                    // the nanoseconds are always positive, so -1.5 is 2s before the epoch + 0.5s
+                    DateTime::from_timestamp(
+                        secs.div_euclid(1.0) as i64,
+                        (secs.rem_euclid(1.0) * 1e9) as u32,
+                    )
+                })
+            })
+            .map(|d| d.fixed_offset()),
+        Json::String(s) => {
+            let s = s.trim();
+            DateTime::parse_from_rfc3339(s).ok().or_else(|| {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+                    .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f"))
+                    .or_else(|_| {
+                        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                            .map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default())
+                    })
+                    .ok()
+                    .map(|d| d.and_utc().fixed_offset())
+            })
+        }
+        _ => None,
+    };
+    date.ok_or_else(|| {
+        crate::to_other_error(format!(
+            "can not parse '{}' as a date (expected RFC 3339 string or unix timestamp)",
+            v
+        ))
+    })
+}
+
+fn parse_timezone(
+    tz: &str,
+    date: DateTime<FixedOffset>,
+) -> Result<DateTime<FixedOffset>, RenderError> {
+    match tz.to_lowercase().as_str() {
+        "" => Ok(date),
+        "utc" | "z" => Ok(date.with_timezone(&Utc).fixed_offset()),
+        "local" => Ok(date.with_timezone(&Local).fixed_offset()),
+        _ => DateTime::parse_from_rfc3339(&format!("2000-01-01T00:00:00{}", tz))
+            .map(|d| date.with_timezone(d.offset()))
+            .map_err(|_| {
+                crate::to_other_error(format!(
+                    "timezone '{}' unknown (expected: utc, local or an offset like +02:00)",
+                    tz
+                ))
+            }),
+    }
+}
+
+fn format_date<Tz: TimeZone>(date: &DateTime<Tz>, format: &str) -> Result<String, RenderError>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match format {
+        "" | "rfc3339" => Ok(date.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+        "rfc2822" => Ok(date.to_rfc2822()),
+        _ => {
+            if StrftimeItems::new(format).any(|i| i == Item::Error) {
+                return Err(crate::to_other_error(format!(
+                    "invalid date format '{}' (expected: rfc3339, rfc2822 or a strftime format)",
+                    format
+                )));
+            }
+            Ok(date.format(format).to_string())
+        }
+    }
+}
+
+/// Parse `s` with a strftime `format`, missing time is midnight, missing offset is UTC.
+fn parse_date_with_format(s: &str, format: &str) -> Result<DateTime<FixedOffset>, RenderError> {
+    DateTime::parse_from_str(s, format)
+        .or_else(|_| NaiveDateTime::parse_from_str(s, format).map(|d| d.and_utc().fixed_offset()))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(s, format).map(|d| {
+                d.and_hms_opt(0, 0, 0)
+                    .unwrap_or_default()
+                    .and_utc()
+                    .fixed_offset()
+            })
+        })
+        .map_err(|e| {
+            crate::to_other_error(format!(
+                "can not parse '{}' with format '{}': {}",
+                s, format, e
+            ))
+        })
+}
+
+/// Parse a duration like `"3d"`, `"1h30m"`, `"-2w"`, `"90s"`, `"250ms"`
+fn parse_duration(s: &str) -> Result<TimeDelta, RenderError> {
+    let invalid = || {
+        crate::to_other_error(format!(
+            "invalid duration '{}' (expected a sequence of <number><unit> with unit in: ms, s, m, h, d, w)",
+            s
+        ))
+    };
+    let trimmed = s.trim();
+    let (negative, mut rest) = match trimmed.strip_prefix('-') {
+        Some(r) => (true, r),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    let mut total = TimeDelta::zero();
+    while !rest.is_empty() {
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let value = rest[..digits_end].parse::<i64>().map_err(|_| invalid())?;
+        rest = &rest[digits_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let delta = match &rest[..unit_end] {
+            "ms" => TimeDelta::try_milliseconds(value),
+            "s" => TimeDelta::try_seconds(value),
+            "m" => TimeDelta::try_minutes(value),
+            "h" => TimeDelta::try_hours(value),
+            "d" => TimeDelta::try_days(value),
+            "w" => TimeDelta::try_weeks(value),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+        total = total.checked_add(&delta).ok_or_else(invalid)?;
+        rest = &rest[unit_end..];
+    }
+    Ok(if negative { -total } else { total })
+}
+
+fn duration_in_unit(delta: TimeDelta, unit: &str) -> Result<i64, RenderError> {
+    match unit {
+        "ms" => Ok(delta.num_milliseconds()),
+        "s" => Ok(delta.num_seconds()),
+        "m" => Ok(delta.num_minutes()),
+        "h" => Ok(delta.num_hours()),
+        "d" => Ok(delta.num_days()),
+        "w" => Ok(delta.num_weeks()),
+        _ => Err(crate::to_other_error(format!(
+            "duration unit '{}' unknown (expected: ms, s, m, h, d, w)",
+            unit
+        ))),
+    }
+}
+
+fn find_str_hash<'a>(h: &'a Helper, name: &str, default: &'a str) -> &'a str {
+    h.hash_get(name)
+        .and_then(|v| v.value().as_str())
+        .unwrap_or(default)
+}
+
+#[allow(non_camel_case_types)]
+struct now_fct {
+    clock: Arc<dyn Clock>,
+}
+
+impl HelperDef for now_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let now = parse_timezone(
+            find_str_hash(h, "timezone", "utc"),
+            self.clock.now().fixed_offset(),
+        )?;
+        let result = format_date(&now, find_str_hash(h, "format", "rfc3339"))?;
+        Ok(ScopedJson::Derived(Json::String(result)))
+    }
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "datetime", feature: Some("datetime");
+    "now"() [format: "string" = "rfc3339", timezone: "string" = "utc"] -> "string" {
+        r##"{{ gt (date_to_unix (now)) 1709288430 }}"## => "true"
+    }
+    "date_format"(date: "string|number") [format: "string" = "rfc3339", timezone: "string"] -> "string" {
+        r##"{{ date_format "2024-03-01T10:20:30Z" format="%d/%m/%Y %H:%M" }}"## => "01/03/2024 10:20"
+    }
+    "date_parse"(s: "string") [format: "string"] -> "string" {
+        r##"{{ date_parse "01/03/2024" format="%d/%m/%Y" }}"## => "2024-03-01T00:00:00Z"
+    }
+    "date_add"(date: "string|number", duration: "string") [format: "string" = "rfc3339"] -> "string" {
+        r##"{{ date_add "2024-03-01T10:20:30Z" "3d" }}"## => "2024-03-04T10:20:30Z"
+    }
+    "date_diff"(start: "string|number", end: "string|number") [unit: "string" = "s"] -> "integer" {
+        r##"{{ date_diff "2024-03-01" "2024-03-04" unit="d" }}"## => "3"
+    }
+    "date_to_unix"(date: "string|number") -> "integer" {
+        r##"{{ date_to_unix "2024-03-01T10:20:30Z" }}"## => "1709288430"
+    }
+    "date_from_unix"(secs: "number") [format: "string" = "rfc3339", timezone: "string" = "utc"] -> "string" {
+        r##"{{ date_from_unix 1709288430 }}"## => "2024-03-01T10:20:30Z"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with_clock(handlebars, clock_from_env())
+}
+
+/// Register the datetime helpers with `clock` as the source of `now`
+pub fn register_with_clock(handlebars: &mut impl HelperRegistry, clock: Arc<dyn Clock>) {
+    handlebars.register_helper("now", Box::new(now_fct { clock }));
+    {
+        handlebars_helper!(date_format: |date: Json, {format: str = "rfc3339", timezone: str = ""}| {
+            format_date(&parse_timezone(timezone, parse_date(date)?)?, format)?
+        });
+        handlebars.register_helper("date_format", Box::new(date_format))
+    }
+    {
+        handlebars_helper!(date_parse: |s: str, {format: str = ""}| {
+            let date = if format.is_empty() {
+                parse_date(&Json::String(s.to_owned()))?
+            } else {
+                parse_date_with_format(s, format)?
+            };
+            format_date(&date, "rfc3339")?
+        });
+        handlebars.register_helper("date_parse", Box::new(date_parse))
+    }
+    {
+        handlebars_helper!(date_add: |date: Json, duration: str, {format: str = "rfc3339"}| {
+            let date = parse_date(date)?;
+            let result = date.checked_add_signed(parse_duration(duration)?).ok_or_else(|| {
+                crate::to_other_error(format!("date_add: '{}' + '{}' is out of range", date, duration))
+            })?;
+            format_date(&result, format)?
+        });
+        handlebars.register_helper("date_add", Box::new(date_add))
+    }
+    {
+        handlebars_helper!(date_diff: |start: Json, end: Json, {unit: str = "s"}| {
+            duration_in_unit(parse_date(end)? - parse_date(start)?, unit)?
+        });
+        handlebars.register_helper("date_diff", Box::new(date_diff))
+    }
+    {
+        handlebars_helper!(date_to_unix: |date: Json| parse_date(date)?.timestamp());
+        handlebars.register_helper("date_to_unix", Box::new(date_to_unix))
+    }
+    {
+        handlebars_helper!(date_from_unix: |secs: Json, {format: str = "rfc3339", timezone: str = "utc"}| {
+            if !secs.is_number() {
+                return Err(crate::to_other_error(format!("date_from_unix: '{}' is not a number", secs)));
+            }
+            format_date(&parse_timezone(timezone, parse_date(secs)?)?, format)?
+        });
+        handlebars.register_helper("date_from_unix", Box::new(date_from_unix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_renders;
+    use pretty_assertions::assert_eq;
+    use std::error::Error;
+
+    fn new_hbs_with_fixed_clock<'reg>() -> Handlebars<'reg> {
+        let mut hbs = crate::new_hbs();
+        let now = DateTime::parse_from_rfc3339("2024-03-01T10:20:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        register_with_clock(&mut hbs, Arc::new(FixedClock(now)));
+        hbs
+    }
+
+    #[test]
+    fn test_now() -> Result<(), Box<dyn Error>> {
+        let hbs = new_hbs_with_fixed_clock();
+        let data = serde_json::json!({});
+        for (tmpl, expected) in [
+            (r##"{{ now }}"##, "2024-03-01T10:20:30Z"),
+            (r##"{{ now format="%Y-%m-%d" }}"##, "2024-03-01"),
+            (
+                r##"{{ now timezone="+02:00" }}"##,
+                "2024-03-01T12:20:30+02:00",
+            ),
+            (
+                r##"{{ now format="rfc2822" }}"##,
+                "Fri, 1 Mar 2024 10:20:30 +0000",
+            ),
+            (
+                r##"{{ date_add (now) "30d" format="%Y-%m-%d" }}"##,
+                "2024-03-31",
+            ),
+        ] {
+            assert_eq!(hbs.render_template(tmpl, &data)?, expected, "{}", tmpl);
+        }
+        assert!(hbs
+            .render_template(r##"{{ now format="%Q" }}"##, &data)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_now_with_system_clock() -> Result<(), Box<dyn Error>> {
+        assert_renders![(r##"{{ len (now format="%Y-%m-%d") }}"##, "10")]
+    }
+
+    #[test]
+    fn test_clock_from_source_date_epoch() {
+        assert_eq!(
+            clock_from_source_date_epoch("1709288430").map(|c| c.now().to_rfc3339()),
+            Some("2024-03-01T10:20:30+00:00".to_owned())
+        );
+        assert!(clock_from_source_date_epoch("foo").is_none());
+    }
+
+    #[test]
+    fn test_date_format() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ date_format "2024-03-01T10:20:30+02:00" }}"##,
+                "2024-03-01T10:20:30+02:00"
+            ),
+            (
+                r##"{{ date_format "2024-03-01T10:20:30+02:00" timezone="utc" }}"##,
+                "2024-03-01T08:20:30Z"
+            ),
+            (
+                r##"{{ date_format "2024-03-01" }}"##,
+                "2024-03-01T00:00:00Z"
+            ),
+            (
+                r##"{{ date_format "2024-03-01 10:20:30" }}"##,
+                "2024-03-01T10:20:30Z"
+            ),
+            (r##"{{ date_format 1709288430 format="%Y" }}"##, "2024"),
+        ]
+    }
+
+    #[test]
+    fn test_date_parse() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ date_parse "01/03/2024 10:20" format="%d/%m/%Y %H:%M" }}"##,
+                "2024-03-01T10:20:00Z"
+            ),
+            (
+                r##"{{ date_parse "2024-03-01 10:20 +0200" format="%Y-%m-%d %H:%M %z" }}"##,
+                "2024-03-01T10:20:00+02:00"
+            ),
+            (
+                r##"{{ date_parse "2024-03-01T10:20:30.5Z" }}"##,
+                "2024-03-01T10:20:30.500Z"
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_date_add() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ date_add "2024-03-01T10:20:30Z" "-1h30m" }}"##,
+                "2024-03-01T08:50:30Z"
+            ),
+            (
+                r##"{{ date_add "2024-03-01T10:20:30+02:00" "2w" format="%Y-%m-%d" }}"##,
+                "2024-03-15"
+            ),
+            (
+                r##"{{ date_add "2024-03-01T10:20:30Z" "250ms" }}"##,
+                "2024-03-01T10:20:30.250Z"
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_date_diff() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ date_diff "2024-03-01T10:20:30Z" "2024-03-04T10:20:30Z" }}"##,
+                "259200"
+            ),
+            (
+                r##"{{ date_diff "2024-03-01T10:20:30Z" "2024-03-04T10:20:30Z" unit="d" }}"##,
+                "3"
+            ),
+            (
+                r##"{{ date_diff "2024-03-04" "2024-03-01" unit="h" }}"##,
+                "-72"
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_date_unix() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ date_to_unix "2024-03-01T12:20:30+02:00" }}"##,
+                "1709288430"
+            ),
+            (
+                r##"{{ date_from_unix 1709288430 timezone="-05:00" format="%H:%M" }}"##,
+                "05:20"
+            ),
+            (r##"{{ date_from_unix 1.5 }}"##, "1970-01-01T00:00:01.500Z"),
+            (r##"{{ date_from_unix -1.5 }}"##, "1969-12-31T23:59:58.500Z"),
+            (r##"{{ date_to_unix -1.5 }}"##, "-2"),
+        ]
+    }
+
+    #[test]
+    fn test_parse_duration() -> Result<(), Box<dyn Error>> {
+        assert_eq!(parse_duration("3d")?, TimeDelta::days(3));
+        assert_eq!(
+            parse_duration("1h30m")?,
+            TimeDelta::hours(1) + TimeDelta::minutes(30)
+        );
+        assert_eq!(parse_duration("-2w")?, -TimeDelta::weeks(2));
+        assert_eq!(parse_duration("+90s")?, TimeDelta::seconds(90));
+        for invalid in ["", "3", "d", "3y", "1h30", "-"] {
+            assert!(parse_duration(invalid).is_err(), "{}", invalid);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        let hbs = crate::new_hbs();
+        let data = serde_json::json!({});
+        for tmpl in [
+            r##"{{ date_format "foo" }}"##,
+            r##"{{ date_parse "2024-03-01" format="%d/%m/%Y" }}"##,
+            r##"{{ date_add "2024-03-01" "3y" }}"##,
+            r##"{{ date_diff "2024-03-01" "2024-03-02" unit="y" }}"##,
+            r##"{{ date_from_unix "2024-03-01" }}"##,
+            r##"{{ date_format "2024-03-01" timezone="Mars/Olympus" }}"##,
+        ] {
+            assert!(hbs.render_template(tmpl, &data).is_err(), "{}", tmpl);
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use base64::alphabet::{Alphabet, STANDARD, URL_SAFE};
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use handlebars::{
+    handlebars_helper, Context, Handlebars, Helper, HelperDef, JsonValue as Json, RenderContext,
+    RenderError, RenderErrorReason, ScopedJson,
+};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::file_helpers::{read_file, FsSandbox};
+use crate::Capability;
+use crate::ErrorPolicy;
+use crate::HelperRegistry;
+
+#[derive(Debug, Error)]
+enum EncodingError {
+    #[error("invalid base64 input '{input}'")]
+    InvalidBase64 {
+        input: String,
+        source: base64::DecodeError,
+    },
+    #[error("invalid hex input '{input}' (expected an even number of hexadecimal digits)")]
+    InvalidHex { input: String },
+    #[error("decoded value of '{input}' is not a valid UTF-8 string")]
+    InvalidUtf8 {
+        input: String,
+        source: std::string::FromUtf8Error,
+    },
+}
+
+/// Characters kept by `encodeURIComponent` (javascript)
+const URL_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+/// Characters kept by `encodeURI` (javascript): the component ones and the reserved of the URL
+const URL_FULL: &AsciiSet = &URL_COMPONENT
+    .remove(b';')
+    .remove(b',')
+    .remove(b'/')
+    .remove(b'?')
+    .remove(b':')
+    .remove(b'@')
+    .remove(b'&')
+    .remove(b'=')
+    .remove(b'+')
+    .remove(b'$')
+    .remove(b'#');
+
+fn base64_engine(url_safe: bool, padding: bool) -> GeneralPurpose {
+    let alphabet: &Alphabet = if url_safe { &URL_SAFE } else { &STANDARD };
+    GeneralPurpose::new(
+        alphabet,
+        GeneralPurposeConfig::new()
+            .with_encode_padding(padding)
+            .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    )
+}
+
+fn to_utf8(input: &str, bytes: Vec<u8>) -> Result<String, EncodingError> {
+    String::from_utf8(bytes).map_err(|source| EncodingError::InvalidUtf8 {
+        input: input.to_owned(),
+        source,
+    })
+}
+
+fn base64_decode(input: &str, url_safe: bool) -> Result<String, EncodingError> {
+    let bytes = base64_engine(url_safe, true)
+        .decode(input.trim())
+        .map_err(|source| EncodingError::InvalidBase64 {
+            input: input.to_owned(),
+            source,
+        })?;
+    to_utf8(input, bytes)
+}
+
+pub(crate) fn hex_encode(bytes: &[u8], uppercase: bool) -> String {
+    bytes
+        .iter()
+        .map(|b| {
+            if uppercase {
+                format!("{:02X}", b)
+            } else {
+                format!("{:02x}", b)
+            }
+        })
+        .collect()
+}
+
+fn hex_decode(input: &str) -> Result<String, EncodingError> {
+    let invalid = || EncodingError::InvalidHex {
+        input: input.to_owned(),
+    };
+    let digits = input.trim().as_bytes();
+    if !digits.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let bytes = digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    to_utf8(input, bytes)
+}
+
+fn url_encode(input: &str, full: bool) -> String {
+    let set = if full { URL_FULL } else { URL_COMPONENT };
+    utf8_percent_encode(input, set).to_string()
+}
+
+fn url_decode(input: &str) -> Result<String, EncodingError> {
+    to_utf8(input, percent_decode_str(input).collect())
+}
+
+#[allow(non_camel_case_types)]
+struct base64_file_fct {
+    policy: ErrorPolicy,
+    sandbox: Option<Arc<FsSandbox>>,
+}
+
+impl HelperDef for base64_file_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let path = h
+            .param(0)
+            .and_then(|v| v.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("base64_file", 0))?;
+        let flag = |name: &str, default: bool| {
+            h.hash_get(name)
+                .and_then(|v| v.value().as_bool())
+                .unwrap_or(default)
+        };
+        let bytes = read_file(
+            "base64_file",
+            path,
+            self.policy,
+            self.sandbox.as_deref(),
+            |p| std::fs::read(p),
+        )?;
+        let encoded = base64_engine(flag("url_safe", false), flag("padding", true)).encode(bytes);
+        Ok(ScopedJson::Derived(Json::String(encoded)))
+    }
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "encoding", feature: Some("encoding");
+    "base64_encode"(s: "string") [url_safe: "boolean" = "false", padding: "boolean" = "true"] -> "string" {
+        r##"{{ base64_encode "hello?>" url_safe=true padding=false }}"## => "aGVsbG8_Pg"
+    }
+    "base64_decode"(s: "string") [url_safe: "boolean" = "false"] -> "string" {
+        r##"{{ base64_decode "aGVsbG8/Pg==" }}"## => "hello?>"
+    }
+    "base64_file"(path: "string") [url_safe: "boolean" = "false", padding: "boolean" = "true"] -> "string", requires FsRead {
+        r##"{{ base64_file "file/does/not/exist" }}"## => ""
+    }
+    "hex_encode"(s: "string") [uppercase: "boolean" = "false"] -> "string" {
+        r##"{{ hex_encode "hello" }}"## => "68656c6c6f"
+    }
+    "hex_decode"(s: "string") -> "string" {
+        r##"{{ hex_decode "68656c6c6f" }}"## => "hello"
+    }
+    "url_encode"(s: "string") [full: "boolean" = "false"] -> "string" {
+        r##"{{ url_encode "a b&c=d/é" }}"## => "a%20b%26c%3Dd%2F%C3%A9"
+    }
+    "url_decode"(s: "string") -> "string" {
+        r##"{{ url_decode "a%20b%26c" }}"## => "a b&c"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with_policy(handlebars, ErrorPolicy::default())
+}
+
+pub fn register_with_policy(handlebars: &mut impl HelperRegistry, policy: ErrorPolicy) {
+    register_with_sandbox(handlebars, policy, None)
+}
+
+/// Register the encoding helpers, `base64_file` is restricted to the paths allowed by `sandbox` (if any)
+pub fn register_with_sandbox(
+    handlebars: &mut impl HelperRegistry,
+    policy: ErrorPolicy,
+    sandbox: Option<Arc<FsSandbox>>,
+) {
+    {
+        handlebars_helper!(base64_encode: |v: str, {url_safe: bool = false, padding: bool = true}| {
+            base64_engine(url_safe, padding).encode(v)
+        });
+        handlebars.register_helper("base64_encode", Box::new(base64_encode))
+    }
+    {
+        handlebars_helper!(base64_decode_fct: |v: str, {url_safe: bool = false}| {
+            base64_decode(v, url_safe).map_err(crate::to_nested_error)?
+        });
+        handlebars.register_helper("base64_decode", Box::new(base64_decode_fct))
+    }
+    handlebars.register_helper_requiring(
+        Capability::FsRead,
+        "base64_file",
+        Box::new(base64_file_fct { policy, sandbox }),
+    );
+    {
+        handlebars_helper!(hex_encode_fct: |v: str, {uppercase: bool = false}| hex_encode(v.as_bytes(), uppercase));
+        handlebars.register_helper("hex_encode", Box::new(hex_encode_fct))
+    }
+    {
+        handlebars_helper!(hex_decode_fct: |v: str| hex_decode(v).map_err(crate::to_nested_error)?);
+        handlebars.register_helper("hex_decode", Box::new(hex_decode_fct))
+    }
+    {
+        handlebars_helper!(url_encode_fct: |v: str, {full: bool = false}| url_encode(v, full));
+        handlebars.register_helper("url_encode", Box::new(url_encode_fct))
+    }
+    {
+        handlebars_helper!(url_decode_fct: |v: str| url_decode(v).map_err(crate::to_nested_error)?);
+        handlebars.register_helper("url_decode", Box::new(url_decode_fct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_renders;
+    use std::error::Error;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_base64() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ base64_encode "" }}"##, r##""##),
+            (r##"{{ base64_encode "hello?>" }}"##, r##"aGVsbG8/Pg=="##),
+            (
+                r##"{{ base64_encode "hello?>" url_safe=true }}"##,
+                r##"aGVsbG8_Pg=="##
+            ),
+            (r##"{{ base64_decode "aGVsbG8/Pg" }}"##, r##"hello?>"##),
+            (
+                r##"{{ base64_decode "aGVsbG8_Pg" url_safe=true }}"##,
+                r##"hello?>"##
+            ),
+            (
+                r##"{{ base64_decode (base64_encode "l'été 日本") }}"##,
+                r##"l'été 日本"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_base64_file() -> Result<(), Box<dyn Error>> {
+        let mut file = NamedTempFile::new()?;
+        file.write_all(&[0u8, 159, 146, 150, 255])?;
+        assert_renders![
+            (r##"{{ base64_file "/file/not/exists" }}"##, ""),
+            (
+                &format!("{{{{ base64_file {:?} }}}}", file.path()),
+                "AJ+Slv8="
+            ),
+            (
+                &format!(
+                    "{{{{ base64_file {:?} url_safe=true padding=false }}}}",
+                    file.path()
+                ),
+                "AJ-Slv8"
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_hex() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ hex_encode "é" uppercase=true }}"##, r##"C3A9"##),
+            (r##"{{ hex_decode "C3A9" }}"##, r##"é"##),
+            (r##"{{ hex_decode "" }}"##, r##""##),
+        ]
+    }
+
+    #[test]
+    fn test_url() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ url_encode "a b&c=d/é?" }}"##,
+                r##"a%20b%26c%3Dd%2F%C3%A9%3F"##
+            ),
+            (
+                r##"{{ url_encode "it's-(ok)_~!*." }}"##,
+                r##"it's-(ok)_~!*."##
+            ),
+            (
+                r##"{{ url_encode "http://h/p a?q=1&r=é#f" full=true }}"##,
+                r##"http://h/p%20a?q=1&r=%C3%A9#f"##
+            ),
+            (
+                r##"{{ url_decode "a%20b%26c%3Dd%2F%C3%A9%3F" }}"##,
+                r##"a b&c=d/é?"##
+            ),
+            (r##"{{ url_decode "a+b" }}"##, r##"a+b"##),
+        ]
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let hbs = crate::new_hbs();
+        let data = ();
+        for tmpl in [
+            r##"{{ base64_decode "a" }}"##,
+            r##"{{ base64_decode "aGVsbG8_Pg" }}"##,
+            r##"{{ base64_decode "/w==" }}"##,
+            r##"{{ hex_decode "abc" }}"##,
+            r##"{{ hex_decode "zz" }}"##,
+            r##"{{ hex_decode "ff" }}"##,
+            r##"{{ url_decode "%ff" }}"##,
+        ] {
+            assert!(hbs.render_template(tmpl, &data).is_err(), "{}", tmpl);
+        }
+    }
+
+    #[test]
+    fn test_base64_file_strict() {
+        let mut hbs = handlebars::Handlebars::new();
+        crate::setup_handlebars_with_policy(&mut hbs, crate::ErrorPolicy::Strict);
+        let data = ();
+        assert!(hbs
+            .render_template(r##"{{ base64_file "/file/not/exists" }}"##, &data)
+            .is_err());
+    }
+}
@@ -1,8 +1,41 @@
-use handlebars::{handlebars_helper, Handlebars};
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, JsonRender, JsonValue as Json, RenderContext,
+    RenderError, RenderErrorReason, ScopedJson,
+};
 
-fn env_var_fct<T: AsRef<str>>(key: T) -> String {
-    let key = key.as_ref();
-    match key {
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::Capability;
+use crate::ErrorPolicy;
+use crate::HelperRegistry;
+
+/// The environment variables read by the helpers (`env_var`, `SOURCE_DATE_EPOCH` for `now`),
+/// see [`crate::HelpersConfig::environment`]
+pub trait Environment: Send + Sync {
+    fn var(&self, key: &str) -> Option<String>;
+}
+
+/// The environment variables of the process (the default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnvironment;
+
+impl Environment for ProcessEnvironment {
+    fn var(&self, key: &str) -> Option<String> {
+        std::env::var(key).ok()
+    }
+}
+
+/// A fixed set of variables (eg to expose only some variables, or in tests)
+impl Environment for HashMap<String, String> {
+    fn var(&self, key: &str) -> Option<String> {
+        self.get(key).cloned()
+    }
+}
+
+fn env_var_value(env: &dyn Environment, key: &str) -> Result<String, std::env::VarError> {
+    let v = match key {
         "ARCH" => std::env::consts::ARCH.to_owned(),
         "DLL_EXTENSION" => std::env::consts::DLL_EXTENSION.to_owned(),
         "DLL_PREFIX" => std::env::consts::DLL_PREFIX.to_owned(),
@@ -11,37 +44,84 @@ fn env_var_fct<T: AsRef<str>>(key: T) -> String {
         "EXE_SUFFIX" => std::env::consts::EXE_SUFFIX.to_owned(),
         "FAMILY" => std::env::consts::FAMILY.to_owned(),
         "OS" => std::env::consts::OS.to_owned(),
-        "USERNAME" => std::env::var("USERNAME")
-            .or_else(|_| std::env::var("username"))
-            .or_else(|_| std::env::var("USER"))
-            .or_else(|_| std::env::var("user"))
-            .unwrap_or_else(|_| "noname".to_owned()),
-        _ => {
-            match std::env::var(key) {
-                Ok(s) => s,
-                Err(e) => {
-                    //TODO better error handler
-                    log::info!(
-                        "helper: env_var failed for key '{:?}' with error '{:?}'",
-                        key,
-                        e
-                    );
-                    "".to_owned()
-                }
-            }
-        }
+        "USERNAME" => ["USERNAME", "username", "USER", "user"]
+            .into_iter()
+            .find_map(|k| env.var(k))
+            .unwrap_or_else(|| "noname".to_owned()),
+        _ => env.var(key).ok_or(std::env::VarError::NotPresent)?,
+    };
+    Ok(v)
+}
+
+#[allow(non_camel_case_types)]
+struct env_var_fct {
+    policy: ErrorPolicy,
+    env: Arc<dyn Environment>,
+}
+
+impl HelperDef for env_var_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let key = h
+            .param(0)
+            .and_then(|v| v.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("env_var", 0))?;
+        let v = match (env_var_value(self.env.as_ref(), key), h.hash_get("default")) {
+            (Ok(v), _) => v,
+            (Err(_), Some(default)) => default.value().render(),
+            (Err(e), None) => self.policy.recover(
+                log::Level::Info,
+                "".to_owned(),
+                format!(
+                    "helper: env_var failed for key '{}' with error '{}'",
+                    key, e
+                ),
+            )?,
+        };
+        Ok(ScopedJson::Derived(Json::String(v)))
     }
 }
 
-pub fn register(handlebars: &mut Handlebars) {
-    handlebars_helper!(env_var: |v: str| env_var_fct(v));
-    handlebars.register_helper("env_var", Box::new(env_var))
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "env", feature: None;
+    "env_var"(name: "string") [default: "string"] -> "string", requires Env {
+        r##"{{ env_var "MY_UNDEFINED_VERSION" default="0.0.0" }}"## => "0.0.0"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with_policy(handlebars, ErrorPolicy::default())
+}
+
+pub fn register_with_policy(handlebars: &mut impl HelperRegistry, policy: ErrorPolicy) {
+    register_with_environment(handlebars, policy, Arc::new(ProcessEnvironment))
+}
+
+/// Register `env_var` reading the variables from `env`
+pub fn register_with_environment(
+    handlebars: &mut impl HelperRegistry,
+    policy: ErrorPolicy,
+    env: Arc<dyn Environment>,
+) {
+    handlebars.register_helper_requiring(
+        Capability::Env,
+        "env_var",
+        Box::new(env_var_fct { policy, env }),
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use crate::tests::assert_helpers;
+    use crate::ErrorPolicy;
+    use std::collections::HashMap;
     use std::error::Error;
+    use std::sync::Arc;
 
     #[test]
     fn test_register_env_helpers() -> Result<(), Box<dyn Error>> {
@@ -53,6 +133,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_env_var_default_and_strict() -> Result<(), Box<dyn Error>> {
+        let env = HashMap::from([("KEY_STRICT".to_owned(), "VALUE".to_owned())]);
+        let mut hbs = handlebars::Handlebars::new();
+        super::register_with_environment(&mut hbs, ErrorPolicy::Strict, Arc::new(env));
+        let data = ();
+        for (tmpl, expected) in [
+            (r##"{{ env_var "KEY_STRICT" }}"##, "VALUE"),
+            (r##"{{ env_var "KEY_STRICT" default="foo" }}"##, "VALUE"),
+            (
+                r##"{{ env_var "A_DO_NOT_EXIST_ENVVAR" default="foo" }}"##,
+                "foo",
+            ),
+            (r##"{{ env_var "A_DO_NOT_EXIST_ENVVAR" default="" }}"##, ""),
+        ] {
+            assert_eq!(hbs.render_template(tmpl, &data)?, expected, "{}", tmpl);
+        }
+        let err = hbs
+            .render_template(r##"{{ env_var "A_DO_NOT_EXIST_ENVVAR" }}"##, &data)
+            .unwrap_err();
+        assert!(err.to_string().contains("A_DO_NOT_EXIST_ENVVAR"), "{}", err);
+        Ok(())
+    }
+
     #[test]
     fn test_env_consts() -> Result<(), Box<dyn Error>> {
         let key = "OS";
@@ -0,0 +1,301 @@
+use handlebars::handlebars_helper;
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::HelperRegistry;
+
+/// Quote for POSIX shell (with single quotes), safe words are left unquoted
+fn escape_shell(s: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c);
+    if !s.is_empty() && s.chars().all(is_safe) {
+        s.to_owned()
+    } else {
+        format!("'{}'", s.replace('\'', r#"'\''"#))
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#x27;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Escape the content of a double-quoted string, `escape_char` returns the escape sequence
+/// of the special characters (`None` to keep the char as is)
+fn escape_with(s: &str, escape_char: impl Fn(char) -> Option<String>) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match escape_char(c) {
+            Some(escaped) => result.push_str(&escaped),
+            None => result.push(c),
+        }
+    }
+    result
+}
+
+fn escape_json_string(s: &str) -> String {
+    escape_with(s, |c| match c {
+        '"' => Some(r#"\""#.to_owned()),
+        '\\' => Some(r#"\\"#.to_owned()),
+        '\n' => Some(r#"\n"#.to_owned()),
+        '\r' => Some(r#"\r"#.to_owned()),
+        '\t' => Some(r#"\t"#.to_owned()),
+        '\u{08}' => Some(r#"\b"#.to_owned()),
+        '\u{0c}' => Some(r#"\f"#.to_owned()),
+        c if c.is_control() && (c as u32) < 0x20 => Some(format!("\\u{:04x}", c as u32)),
+        _ => None,
+    })
+}
+
+fn escape_yaml_string(s: &str) -> String {
+    escape_with(s, |c| match c {
+        '"' => Some(r#"\""#.to_owned()),
+        '\\' => Some(r#"\\"#.to_owned()),
+        '\n' => Some(r#"\n"#.to_owned()),
+        '\r' => Some(r#"\r"#.to_owned()),
+        '\t' => Some(r#"\t"#.to_owned()),
+        '\0' => Some(r#"\0"#.to_owned()),
+        c if c.is_control() => Some(format!("\\u{:04x}", c as u32)),
+        _ => None,
+    })
+}
+
+fn escape_toml_string(s: &str) -> String {
+    escape_with(s, |c| match c {
+        '"' => Some(r#"\""#.to_owned()),
+        '\\' => Some(r#"\\"#.to_owned()),
+        '\n' => Some(r#"\n"#.to_owned()),
+        '\r' => Some(r#"\r"#.to_owned()),
+        '\t' => Some(r#"\t"#.to_owned()),
+        '\u{08}' => Some(r#"\b"#.to_owned()),
+        '\u{0c}' => Some(r#"\f"#.to_owned()),
+        c if c.is_control() => Some(format!("\\u{:04X}", c as u32)),
+        _ => None,
+    })
+}
+
+fn escape_rust_string(s: &str) -> String {
+    escape_with(s, |c| match c {
+        '"' => Some(r#"\""#.to_owned()),
+        '\\' => Some(r#"\\"#.to_owned()),
+        '\n' => Some(r#"\n"#.to_owned()),
+        '\r' => Some(r#"\r"#.to_owned()),
+        '\t' => Some(r#"\t"#.to_owned()),
+        '\0' => Some(r#"\0"#.to_owned()),
+        c if c.is_control() => Some(format!("\\u{{{:x}}}", c as u32)),
+        _ => None,
+    })
+}
+
+/// Escape the meta characters of a regular expression (same set as `regex::escape`)
+fn escape_regex(s: &str) -> String {
+    escape_with(s, |c| {
+        r"\.+*?()|[]{}^$#&-~"
+            .contains(c)
+            .then(|| format!("\\{}", c))
+    })
+}
+
+/// Quote as a SQL string literal (single quotes doubled)
+fn escape_sql_literal(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Quote a CSV field (RFC 4180) when it contains a separator, a quote or a line break
+fn escape_csv(s: &str, separator: &str) -> String {
+    if s.contains(['"', '\n', '\r']) || (!separator.is_empty() && s.contains(separator)) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "escape", feature: Some("string");
+    "escape_shell"(s: "string") -> "string" {
+        r##"{{ escape_shell "it's $HOME" }}"## => r##"'it'\''s $HOME'"##
+    }
+    "escape_html"(s: "string") -> "string" {
+        r##"{{ escape_html "<b>Tom & Jerry's</b>" }}"## => "&lt;b&gt;Tom &amp; Jerry&#x27;s&lt;/b&gt;"
+    }
+    "escape_xml"(s: "string") -> "string" {
+        r##"{{ escape_xml "<a b='c'/>" }}"## => "&lt;a b=&apos;c&apos;/&gt;"
+    }
+    "escape_json_string"(s: "string") -> "string" {
+        r##""{{ escape_json_string "say \"hi\"\n" }}""## => r##""say \"hi\"\n""##
+    }
+    "escape_yaml_string"(s: "string") -> "string" {
+        r##""{{ escape_yaml_string "a: \"b\"" }}""## => r##""a: \"b\"""##
+    }
+    "escape_toml_string"(s: "string") -> "string" {
+        r##""{{ escape_toml_string "C:\\dir" }}""## => r##""C:\\dir""##
+    }
+    "escape_regex"(s: "string") -> "string" {
+        r##"{{ escape_regex "1+1=2?" }}"## => r##"1\+1=2\?"##
+    }
+    "escape_sql_literal"(s: "string") -> "string" {
+        r##"{{ escape_sql_literal "O'Reilly" }}"## => "'O''Reilly'"
+    }
+    "escape_rust_string"(s: "string") -> "string" {
+        r##""{{ escape_rust_string "tab\t" }}""## => r##""tab\t""##
+    }
+    "escape_csv"(s: "string") [separator: "string" = ","] -> "string" {
+        r##"{{ escape_csv "say \"hi\", bye" }}"## => r##""say ""hi"", bye""##
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    {
+        handlebars_helper!(escape_shell_fct: |v: str| escape_shell(v));
+        handlebars.register_helper("escape_shell", Box::new(escape_shell_fct))
+    }
+    {
+        handlebars_helper!(escape_html_fct: |v: str| escape_html(v));
+        handlebars.register_helper("escape_html", Box::new(escape_html_fct))
+    }
+    {
+        handlebars_helper!(escape_xml_fct: |v: str| escape_xml(v));
+        handlebars.register_helper("escape_xml", Box::new(escape_xml_fct))
+    }
+    {
+        handlebars_helper!(escape_json_string_fct: |v: str| escape_json_string(v));
+        handlebars.register_helper("escape_json_string", Box::new(escape_json_string_fct))
+    }
+    {
+        handlebars_helper!(escape_yaml_string_fct: |v: str| escape_yaml_string(v));
+        handlebars.register_helper("escape_yaml_string", Box::new(escape_yaml_string_fct))
+    }
+    {
+        handlebars_helper!(escape_toml_string_fct: |v: str| escape_toml_string(v));
+        handlebars.register_helper("escape_toml_string", Box::new(escape_toml_string_fct))
+    }
+    {
+        handlebars_helper!(escape_regex_fct: |v: str| escape_regex(v));
+        handlebars.register_helper("escape_regex", Box::new(escape_regex_fct))
+    }
+    {
+        handlebars_helper!(escape_sql_literal_fct: |v: str| escape_sql_literal(v));
+        handlebars.register_helper("escape_sql_literal", Box::new(escape_sql_literal_fct))
+    }
+    {
+        handlebars_helper!(escape_rust_string_fct: |v: str| escape_rust_string(v));
+        handlebars.register_helper("escape_rust_string", Box::new(escape_rust_string_fct))
+    }
+    {
+        handlebars_helper!(escape_csv_fct: |v: str, {separator: str = ","}| escape_csv(v, separator));
+        handlebars.register_helper("escape_csv", Box::new(escape_csv_fct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_renders;
+    use std::error::Error;
+
+    #[test]
+    fn test_escape_shell() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ escape_shell "foo" }}"##, r##"foo"##),
+            (r##"{{ escape_shell "a/b-c_1.txt" }}"##, r##"a/b-c_1.txt"##),
+            (r##"{{ escape_shell "" }}"##, r##"''"##),
+            (
+                r##"{{ escape_shell "hello world" }}"##,
+                r##"'hello world'"##
+            ),
+            (r##"{{ escape_shell "a;rm -rf /" }}"##, r##"'a;rm -rf /'"##),
+        ]
+    }
+
+    #[test]
+    fn test_escape_html_xml() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ escape_html "<a href=\"x\">Tom & Jerry's</a>" }}"##,
+                r##"&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#x27;s&lt;/a&gt;"##
+            ),
+            (
+                r##"{{ escape_xml "<a b='c'>&</a>" }}"##,
+                r##"&lt;a b=&apos;c&apos;&gt;&amp;&lt;/a&gt;"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_escape_string_literals() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##""{{ escape_json_string "say \"hi\"\n\tC:\\" }}""##,
+                r##""say \"hi\"\n\tC:\\""##
+            ),
+            (r##"{{ escape_json_string "é😀" }}"##, r##"é😀"##),
+            (
+                r##""{{ escape_yaml_string "a: \"b\"\n#c\\" }}""##,
+                r##""a: \"b\"\n#c\\""##
+            ),
+            (
+                r##""{{ escape_toml_string "path = \"C:\\dir\"\n" }}""##,
+                r##""path = \"C:\\dir\"\n""##
+            ),
+            (
+                r##""{{ escape_rust_string "println!(\"{}\\n\", x);\n" }}""##,
+                r##""println!(\"{}\\n\", x);\n""##
+            ),
+            (r##"{{ escape_rust_string "l'été" }}"##, r##"l'été"##),
+        ]
+    }
+
+    #[test]
+    fn test_escape_regex() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ escape_regex "a.b*c" }}"##, r##"a\.b\*c"##),
+            (
+                r##"{{ escape_regex "(1+1)=[2]?" }}"##,
+                r##"\(1\+1\)=\[2\]\?"##
+            ),
+            (r##"{{ escape_regex "^$|{}\\" }}"##, r##"\^\$\|\{\}\\"##),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_escape_regex_same_as_regex_escape() {
+        let s = r"\.+*?()|[]{}^$#&-~ foo bar 42";
+        assert_eq!(super::escape_regex(s), regex::escape(s));
+    }
+
+    #[test]
+    fn test_escape_sql_csv() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ escape_sql_literal "" }}"##, r##"''"##),
+            (r##"{{ escape_csv "foo" }}"##, r##"foo"##),
+            (r##"{{ escape_csv "a,b" }}"##, r##""a,b""##),
+            (r##"{{ escape_csv "say \"hi\"" }}"##, r##""say ""hi""""##),
+            (r##"{{ escape_csv "a\nb" }}"##, "\"a\nb\""),
+            (r##"{{ escape_csv "a,b" separator=";" }}"##, r##"a,b"##),
+            (r##"{{ escape_csv "a;b" separator=";" }}"##, r##""a;b""##),
+        ]
+    }
+}
@@ -1,27 +1,654 @@
-use handlebars::{handlebars_helper, Handlebars};
-use std::path::Path;
-
-pub fn register(handlebars: &mut Handlebars) {
-    handlebars_helper!(read_to_str: |v: str| {
-        let p = Path::new(v);
-        if p.exists() {
-            std::fs::read_to_string(p)?
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, JsonValue as Json, RenderContext, RenderError,
+    RenderErrorReason, ScopedJson,
+};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+use globset::{GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::{Capabilities, Capability, ErrorPolicy, HelperRegistry};
+
+#[derive(Debug, Error)]
+pub enum FsSandboxError {
+    #[error("access to '{path}' denied: it resolves to '{}', outside of the sandbox root '{}' and of the allowed directories", resolved.display(), root.display())]
+    OutsideSandbox {
+        path: String,
+        resolved: PathBuf,
+        root: PathBuf,
+    },
+    #[error("failed to resolve the path '{path}'")]
+    Unresolvable {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// Restrict the paths accessible by the helpers (`read_to_str`, `read_dir`, `glob`, `base64_file`,
+/// `file_sha256`, `canonicalize`,...) to a root directory and to a list of allowed directories.
+///
+/// Paths are resolved (relative to the current directory) with the symlinks followed and the `..`
+/// applied, so `root/../secret` or a symlink inside the root to a file outside are rejected.
+/// A `..` following a not existing directory (eg `root/nope/../link`) is rejected too.
+///
+/// ```rust
+/// use handlebars_misc_helpers::file_helpers::FsSandbox;
+///
+/// let sandbox = FsSandbox::new(".")?.allow(std::env::temp_dir())?;
+/// assert!(sandbox.check("Cargo.toml").is_ok());
+/// assert!(sandbox.check("../outside").is_err());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct FsSandbox {
+    root: PathBuf,
+    allowed: Vec<PathBuf>,
+}
+
+impl FsSandbox {
+    /// Create a sandbox with `root` (that should exist) as root directory
+    pub fn new<P: AsRef<Path>>(root: P) -> std::io::Result<Self> {
+        Ok(Self {
+            root: root.as_ref().canonicalize()?,
+            allowed: vec![],
+        })
+    }
+
+    /// Allow the access to the directory `dir` (that should exist) in addition to the root
+    pub fn allow<P: AsRef<Path>>(mut self, dir: P) -> std::io::Result<Self> {
+        self.allowed.push(dir.as_ref().canonicalize()?);
+        Ok(self)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The root or the allowed directory that contains the resolved `path`
+    fn top_of(&self, path: &Path) -> Option<&Path> {
+        std::iter::once(&self.root)
+            .chain(&self.allowed)
+            .find(|dir| path.starts_with(dir))
+            .map(PathBuf::as_path)
+    }
+
+    /// Resolve `path` and check that it's inside the root or an allowed directory
+    pub fn check(&self, path: &str) -> Result<PathBuf, FsSandboxError> {
+        let resolved = resolve(Path::new(path)).map_err(|source| FsSandboxError::Unresolvable {
+            path: path.to_owned(),
+            source,
+        })?;
+        if std::iter::once(&self.root)
+            .chain(&self.allowed)
+            .any(|dir| resolved.starts_with(dir))
+        {
+            Ok(resolved)
         } else {
-            log::warn!(
-                "helper: read_to_str failed for non existing path path '{:?}'",
-                v
-            );
-            "".to_owned()
+            Err(FsSandboxError::OutsideSandbox {
+                path: path.to_owned(),
+                resolved,
+                root: self.root.clone(),
+            })
+        }
+    }
+}
+
+/// Absolute path of `path`, resolved component by component like the OS does: every existing
+/// component is canonicalized (symlinks followed) before applying the next one, so a `..` is
+/// applied to the real parent. The not existing part is appended as is, a `..` inside it
+/// (eg `nope/../link`) or a dangling symlink is an error.
+fn resolve(path: &Path) -> std::io::Result<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    let mut resolved = PathBuf::new();
+    let mut missing = false;
+    for component in absolute.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => resolved.push(component),
+            Component::CurDir => {}
+            Component::ParentDir if missing => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("'..' after the not existing '{}'", resolved.display()),
+                ));
+            }
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(c) => {
+                resolved.push(c);
+                if !missing {
+                    match resolved.canonicalize() {
+                        Ok(canonical) => resolved = canonical,
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                            if resolved.symlink_metadata().is_ok() {
+                                return Err(std::io::Error::new(
+                                    std::io::ErrorKind::NotFound,
+                                    format!("dangling symlink '{}'", resolved.display()),
+                                ));
+                            }
+                            missing = true;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Check `path` against the `sandbox` (if any), the returned path is the resolved one
+pub(crate) fn check_path(
+    helper_name: &str,
+    path: &str,
+    sandbox: Option<&FsSandbox>,
+) -> Result<PathBuf, RenderError> {
+    match sandbox {
+        None => Ok(PathBuf::from(path)),
+        Some(sandbox) => sandbox
+            .check(path)
+            .map_err(|e| crate::to_other_error(format!("helper: {} {}", helper_name, e))),
+    }
+}
+
+/// Read the file at `path` with `read`, if the path doesn't exist return the default value (empty)
+/// or an error (according to `policy`). A path outside of the `sandbox` is always an error.
+pub(crate) fn read_file<T: Default>(
+    helper_name: &str,
+    path: &str,
+    policy: ErrorPolicy,
+    sandbox: Option<&FsSandbox>,
+    read: impl FnOnce(&Path) -> std::io::Result<T>,
+) -> Result<T, RenderError> {
+    let p = check_path(helper_name, path, sandbox)?;
+    if p.exists() {
+        Ok(read(&p)?)
+    } else {
+        policy.recover(
+            log::Level::Warn,
+            T::default(),
+            format!(
+                "helper: {} failed for non existing path '{}'",
+                helper_name, path
+            ),
+        )
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct read_to_str_fct {
+    policy: ErrorPolicy,
+    sandbox: Option<Arc<FsSandbox>>,
+}
+
+impl HelperDef for read_to_str_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let path = h
+            .param(0)
+            .and_then(|v| v.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("read_to_str", 0))?;
+        let content = read_file(
+            "read_to_str",
+            path,
+            self.policy,
+            self.sandbox.as_deref(),
+            |p| std::fs::read_to_string(p),
+        )?;
+        Ok(ScopedJson::Derived(Json::String(content)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Listing {
+    ReadDir,
+    Glob,
+    FindFiles,
+}
+
+impl Listing {
+    const ALL: [Listing; 3] = [Listing::ReadDir, Listing::Glob, Listing::FindFiles];
+
+    fn helper_name(&self) -> &'static str {
+        match self {
+            Listing::ReadDir => "read_dir",
+            Listing::Glob => "glob",
+            Listing::FindFiles => "find_files",
+        }
+    }
+}
+
+/// `read_dir`, `glob`, `find_files`: the results are sorted (by name or by path) to render
+/// the same output on every run
+#[allow(non_camel_case_types)]
+struct listing_fct {
+    listing: Listing,
+    policy: ErrorPolicy,
+    sandbox: Option<Arc<FsSandbox>>,
+    /// the global excludes file of git is located with the environment variables
+    env: bool,
+}
+
+impl HelperDef for listing_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let helper_name = self.listing.helper_name();
+        let path = h
+            .param(0)
+            .and_then(|v| v.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex(helper_name, 0))?;
+        let sandbox = self.sandbox.as_deref();
+        let res = match self.listing {
+            Listing::ReadDir => read_file(helper_name, path, self.policy, sandbox, |p| {
+                read_dir(path, p, sandbox)
+            })?,
+            Listing::Glob => glob(path, sandbox)?,
+            Listing::FindFiles => {
+                let include = find_globs(h, "include")?;
+                let exclude = find_globs(h, "exclude")?;
+                let gitignore = h
+                    .hash_get("gitignore")
+                    .and_then(|v| v.value().as_bool())
+                    .unwrap_or(true);
+                let global = if gitignore && self.env {
+                    global_excludes_file().filter(|global| {
+                        sandbox
+                            .is_none_or(|sandbox| sandbox.check(&global.to_string_lossy()).is_ok())
+                    })
+                } else {
+                    None
+                };
+                read_file(helper_name, path, self.policy, sandbox, |p| {
+                    find_files(
+                        path,
+                        p,
+                        &include,
+                        &exclude,
+                        gitignore.then_some(IgnoreScope { sandbox, global }),
+                    )
+                })?
+            }
+        };
+        Ok(ScopedJson::Derived(Json::Array(res)))
+    }
+}
+
+/// The names of the entries of `dir` (sorted) with their type (the symlinks are not followed)
+fn sorted_entries(dir: &Path) -> std::io::Result<Vec<(String, std::fs::FileType)>> {
+    let mut entries = std::fs::read_dir(dir)?
+        .map(|entry| {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            Ok((name, entry.file_type()?))
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
+
+/// `dir` and `name` joined with a `/` (whatever the OS, to render the same output)
+fn join_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_owned()
+    } else if dir.ends_with('/') {
+        format!("{}{}", dir, name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// Walk the tree under `dir` (the symlinks to directories are not followed, the directories
+/// ignored by `rules` are skipped), `visit` is called with the path relative to `dir` (prefixed
+/// by `rel`), the path and if it's a directory, it returns `false` to skip a directory
+fn walk(
+    dir: &Path,
+    rel: &str,
+    max_depth: Option<usize>,
+    mut rules: Option<&mut IgnoreRules>,
+    visit: &mut dyn FnMut(&str, &Path, bool) -> bool,
+) -> std::io::Result<()> {
+    if max_depth == Some(0) {
+        return Ok(());
+    }
+    let len = rules.as_ref().map(|rules| rules.len());
+    if let Some(rules) = rules.as_mut() {
+        rules.add(&dir.join(".gitignore"), dir);
+    }
+    for (name, file_type) in sorted_entries(dir)? {
+        let path = dir.join(&name);
+        let is_dir = file_type.is_dir();
+        if let Some(rules) = rules.as_ref() {
+            if (is_dir && name == ".git") || rules.is_ignored(&path, is_dir) {
+                continue;
+            }
+        }
+        let rel = join_path(rel, &name);
+        if visit(&rel, &path, is_dir) && is_dir {
+            walk(
+                &path,
+                &rel,
+                max_depth.map(|max| max - 1),
+                rules.as_deref_mut(),
+                visit,
+            )?;
+        }
+    }
+    if let (Some(rules), Some(len)) = (rules, len) {
+        rules.truncate(len);
+    }
+    Ok(())
+}
+
+fn read_dir(dir: &str, path: &Path, sandbox: Option<&FsSandbox>) -> std::io::Result<Vec<Json>> {
+    sorted_entries(path)?
+        .into_iter()
+        .map(|(name, file_type)| {
+            let entry_path = join_path(dir, &name);
+            // don't follow a symlink to a path outside of the sandbox
+            let follow = !file_type.is_symlink()
+                || sandbox.is_none_or(|sandbox| sandbox.check(&entry_path).is_ok());
+            let metadata = if follow {
+                std::fs::metadata(path.join(&name))
+                    .or_else(|_| std::fs::symlink_metadata(path.join(&name)))?
+            } else {
+                std::fs::symlink_metadata(path.join(&name))?
+            };
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| Json::from(d.as_secs()))
+                .unwrap_or(Json::Null);
+            Ok(Json::from_iter([
+                ("name", Json::from(name)),
+                ("path", Json::from(entry_path)),
+                ("is_dir", Json::from(metadata.is_dir())),
+                ("size", Json::from(metadata.len())),
+                ("modified", modified),
+            ]))
+        })
+        .collect()
+}
+
+/// Compile `pattern`: `*`, `?` don't match a `/`, `**` matches any number of directories
+fn new_glob(pattern: &str) -> Result<globset::Glob, globset::Error> {
+    GlobBuilder::new(pattern).literal_separator(true).build()
+}
+
+/// The leading segments of `pattern` without any special characters (eg `src/foo` for
+/// `src/foo/**/*.rs`) and the number of those segments
+fn literal_prefix(pattern: &str) -> (String, usize) {
+    let literals: Vec<&str> = pattern
+        .split('/')
+        .take_while(|s| !s.contains(['*', '?', '[', '{', '\\']))
+        .collect();
+    (literals.join("/"), literals.len())
+}
+
+/// The maximum number of segments of the paths matched by `pattern` (`None` if unbounded)
+fn max_depth(pattern: &str) -> Option<usize> {
+    (!pattern.contains("**")).then(|| pattern.split('/').count())
+}
+
+fn glob(pattern: &str, sandbox: Option<&FsSandbox>) -> Result<Vec<Json>, RenderError> {
+    let glob: GlobMatcher = new_glob(pattern)
+        .map_err(|e| crate::to_other_error(format!("helper: glob {}", e)))?
+        .compile_matcher();
+    let (base, literals) = literal_prefix(pattern);
+    let base = if base.is_empty() && literals > 0 {
+        "/".to_owned()
+    } else {
+        base
+    };
+    let path = check_path("glob", if base.is_empty() { "." } else { &base }, sandbox)?;
+    let mut paths = vec![];
+    match max_depth(pattern) {
+        // no special characters
+        Some(depth) if depth == literals => {
+            if path.exists() {
+                paths.push(pattern.to_owned());
+            }
+        }
+        max_depth => {
+            if path.is_dir() {
+                walk(
+                    &path,
+                    &base,
+                    max_depth.map(|max| max - literals),
+                    None,
+                    &mut |rel, _, _| {
+                        if glob.is_match(rel) {
+                            paths.push(rel.to_owned());
+                        }
+                        true
+                    },
+                )?;
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths.into_iter().map(Json::String).collect())
+}
+
+/// The patterns of the hash parameter `name` (an array or a comma separated string),
+/// a pattern without `/` matches the name of the files at any level (like in `.gitignore`)
+fn find_globs(h: &Helper, name: &str) -> Result<GlobSet, RenderError> {
+    let patterns: Vec<&str> = match h.hash_get(name).map(|v| v.value()) {
+        Some(Json::Array(items)) => items.iter().filter_map(|v| v.as_str()).collect(),
+        Some(Json::String(s)) => s.split(',').map(str::trim).collect(),
+        _ => vec![],
+    };
+    let to_error = |e: globset::Error| crate::to_other_error(format!("helper: find_files {}", e));
+    let mut globs = GlobSetBuilder::new();
+    for p in patterns.into_iter().filter(|p| !p.is_empty()) {
+        let glob = if p.contains('/') {
+            new_glob(p.trim_start_matches('/'))
+        } else {
+            new_glob(&format!("**/{}", p))
+        };
+        globs.add(glob.map_err(to_error)?);
+    }
+    globs.build().map_err(to_error)
+}
+
+/// The rules of the `.gitignore` files (the ones of the deeper directories take precedence)
+#[derive(Default)]
+struct IgnoreRules(Vec<Gitignore>);
+
+impl IgnoreRules {
+    /// The rules that apply to `dir` from outside of it, read only from the directories under
+    /// `top`: the `.gitignore` files of the parent directories, the `.git/info/exclude` and the
+    /// `global` excludes file of the repository (if any)
+    fn for_parents_of(dir: &Path, top: &Path, global: Option<&Path>) -> Self {
+        let mut rules = Self::default();
+        let dirs: Vec<&Path> = dir.ancestors().take_while(|d| d.starts_with(top)).collect();
+        if let Some(repo) = dirs.iter().find(|d| d.join(".git").exists()) {
+            if let Some(global) = global {
+                rules.add(global, repo);
+            }
+            rules.add(&repo.join(".git/info/exclude"), repo);
+        }
+        for parent in dirs.iter().skip(1).rev() {
+            rules.add(&parent.join(".gitignore"), parent);
         }
+        rules
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.0.truncate(len)
+    }
+
+    /// Add the rules of the file `path` (if any), relative to the directory `root`
+    fn add(&mut self, path: &Path, root: &Path) {
+        if !path.is_file() {
+            return;
+        }
+        let mut builder = GitignoreBuilder::new(root);
+        // like git, the invalid patterns are skipped
+        let _ = builder.add(path);
+        if let Ok(gitignore) = builder.build() {
+            self.0.push(gitignore);
+        }
+    }
+
+    /// `path` is an absolute path (with the same prefix as the directories of the rules)
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.0
+            .iter()
+            .rev()
+            .map(|gitignore| gitignore.matched(path, is_dir))
+            .find(|m| !m.is_none())
+            .is_some_and(|m| m.is_ignore())
+    }
+}
+
+/// The default location of the global excludes file of git (`core.excludesFile` is not read)
+fn global_excludes_file() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        Some(config) => Some(PathBuf::from(config).join("git/ignore")),
+        None => std::env::var_os("HOME")
+            .filter(|v| !v.is_empty())
+            .map(|home| PathBuf::from(home).join(".config/git/ignore")),
+    }
+}
+
+/// Where `find_files` reads the rules of git: the parent directories up to the root (or the
+/// allowed directory) of the `sandbox`, or none without sandbox, and the `global` excludes file
+struct IgnoreScope<'a> {
+    sandbox: Option<&'a FsSandbox>,
+    global: Option<PathBuf>,
+}
+
+fn find_files(
+    dir: &str,
+    path: &Path,
+    include: &GlobSet,
+    exclude: &GlobSet,
+    gitignore: Option<IgnoreScope>,
+) -> std::io::Result<Vec<Json>> {
+    // the paths of the rules of .gitignore are absolute
+    let path = path.canonicalize()?;
+    let mut rules = gitignore.map(|scope| {
+        let top = scope
+            .sandbox
+            .and_then(|sandbox| sandbox.top_of(&path))
+            .unwrap_or(&path);
+        IgnoreRules::for_parents_of(&path, top, scope.global.as_deref())
     });
-    handlebars.register_helper("read_to_str", Box::new(read_to_str))
+    let prefix = if dir == "." { "" } else { dir };
+    let mut files = vec![];
+    walk(
+        &path,
+        "",
+        None,
+        rules.as_mut(),
+        &mut |rel, entry_path, is_dir| {
+            if exclude.is_match(rel) {
+                return false;
+            }
+            let is_file = !is_dir && entry_path.is_file();
+            if is_file && (include.is_empty() || include.is_match(rel)) {
+                files.push(join_path(prefix, rel));
+            }
+            true
+        },
+    )?;
+    files.sort();
+    Ok(files.into_iter().map(Json::String).collect())
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "file", feature: None;
+    "read_to_str"(path: "string") -> "string", requires FsRead {
+        r##"{{ read_to_str ".markdownlint.yaml" }}"## => "MD013: false\nMD033: false\n", in_repo
+    }
+    "read_dir"(path: "string") -> "array", requires FsRead {
+        r##"{{#each (read_dir "src") }}{{#if (eq name "lib.rs") }}{{ path }} {{ is_dir }}{{/if}}{{/each}}"## => "src/lib.rs false", in_repo
+    }
+    "glob"(pattern: "string") -> "array", requires FsRead {
+        r##"{{#each (glob "src/{lib,catalog}.rs") }}{{ this }};{{/each}}"## => "src/catalog.rs;src/lib.rs;", in_repo
+    }
+    "find_files"(dir: "string") [include: "array|string", exclude: "array|string", gitignore: "boolean" = "true"] -> "array", requires FsRead {
+        r##"{{#each (find_files "src" include=["lib.rs", "catalog.rs"]) }}{{ this }};{{/each}}"## => "src/catalog.rs;src/lib.rs;", in_repo
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with_policy(handlebars, ErrorPolicy::default())
+}
+
+pub fn register_with_policy(handlebars: &mut impl HelperRegistry, policy: ErrorPolicy) {
+    register_with_sandbox(handlebars, policy, None)
+}
+
+/// Register the file helpers, restricted to the paths allowed by `sandbox` (if any)
+pub fn register_with_sandbox(
+    handlebars: &mut impl HelperRegistry,
+    policy: ErrorPolicy,
+    sandbox: Option<Arc<FsSandbox>>,
+) {
+    register_with(handlebars, policy, sandbox, Capabilities::all())
+}
+
+/// Register the file helpers, `find_files` reads the global excludes file of git only if the
+/// `env` capability is granted (the capability `fs_read` is checked by the caller)
+pub fn register_with(
+    handlebars: &mut impl HelperRegistry,
+    policy: ErrorPolicy,
+    sandbox: Option<Arc<FsSandbox>>,
+    capabilities: Capabilities,
+) {
+    handlebars.register_helper_requiring(
+        Capability::FsRead,
+        "read_to_str",
+        Box::new(read_to_str_fct {
+            policy,
+            sandbox: sandbox.clone(),
+        }),
+    );
+    for listing in Listing::ALL {
+        handlebars.register_helper_requiring(
+            Capability::FsRead,
+            listing.helper_name(),
+            Box::new(listing_fct {
+                listing,
+                policy,
+                sandbox: sandbox.clone(),
+                env: capabilities.grants(Capability::Env),
+            }),
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::FsSandbox;
     use crate::assert_renders;
+    use crate::ErrorPolicy;
     use std::error::Error;
     use std::io::Write;
+    use std::sync::Arc;
     use tempfile::NamedTempFile;
 
     #[test]
@@ -39,4 +666,385 @@ mod tests {
             )
         ]
     }
+
+    #[test]
+    fn test_read_to_str_strict() -> Result<(), Box<dyn Error>> {
+        let file_content = "Brian was here. Briefly.";
+        let mut file = NamedTempFile::new()?;
+        write!(file, "{}", file_content)?;
+        let mut hbs = handlebars::Handlebars::new();
+        crate::setup_handlebars_with_policy(&mut hbs, ErrorPolicy::Strict);
+        let data = ();
+        assert_eq!(
+            hbs.render_template(&format!("{{{{ read_to_str {:?} }}}}", file.path()), &data)?,
+            file_content
+        );
+        let err = hbs
+            .render_template(r##"{{ read_to_str "/file/not/exists" }}"##, &data)
+            .unwrap_err();
+        assert!(err.to_string().contains("/file/not/exists"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sandbox_check() -> Result<(), Box<dyn Error>> {
+        let root = tempfile::tempdir()?;
+        let other = tempfile::tempdir()?;
+        std::fs::create_dir(root.path().join("sub"))?;
+        std::fs::write(root.path().join("sub/a.txt"), "a")?;
+        std::fs::write(other.path().join("b.txt"), "b")?;
+        let sandbox = FsSandbox::new(root.path())?;
+        let in_root = |p: &str| root.path().join(p).to_string_lossy().into_owned();
+        assert!(sandbox.check(&in_root("sub/a.txt")).is_ok());
+        assert!(sandbox.check(&in_root("sub/../sub/a.txt")).is_ok());
+        assert!(sandbox.check(&in_root("sub/not_exists/../a.txt")).is_err());
+        assert!(sandbox.check(&in_root("not_exists.txt")).is_ok());
+        assert!(sandbox.check(&in_root("../escape.txt")).is_err());
+        assert!(sandbox.check(&in_root("sub/../../escape.txt")).is_err());
+        assert!(sandbox
+            .check(&in_root("not_exists/../../escape.txt"))
+            .is_err());
+        let other_file = other.path().join("b.txt").to_string_lossy().into_owned();
+        assert!(sandbox.check(&other_file).is_err());
+        assert!(sandbox
+            .clone()
+            .allow(other.path())?
+            .check(&other_file)
+            .is_ok());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sandbox_check_symlink() -> Result<(), Box<dyn Error>> {
+        let root = tempfile::tempdir()?;
+        let other = tempfile::tempdir()?;
+        std::fs::write(other.path().join("secret.txt"), "secret")?;
+        std::os::unix::fs::symlink(other.path(), root.path().join("link_dir"))?;
+        std::os::unix::fs::symlink(
+            other.path().join("secret.txt"),
+            root.path().join("link.txt"),
+        )?;
+        let sandbox = FsSandbox::new(root.path())?;
+        std::os::unix::fs::symlink(
+            other.path().join("not_exists.txt"),
+            root.path().join("dangling.txt"),
+        )?;
+        for p in [
+            "link.txt",
+            "link_dir/secret.txt",
+            "link_dir/not_exists.txt",
+            "not_exists/../link_dir/secret.txt",
+            "link_dir/../secret.txt",
+            "dangling.txt",
+        ] {
+            let p = root.path().join(p).to_string_lossy().into_owned();
+            assert!(sandbox.check(&p).is_err(), "{}", p);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_to_str_sandbox() -> Result<(), Box<dyn Error>> {
+        let root = tempfile::tempdir()?;
+        std::fs::write(root.path().join("a.txt"), "content of a")?;
+        let outside = NamedTempFile::new()?;
+        let mut hbs = crate::new_hbs();
+        super::register_with_sandbox(
+            &mut hbs,
+            ErrorPolicy::Lenient,
+            Some(Arc::new(FsSandbox::new(root.path())?)),
+        );
+        let data = ();
+        let tmpl = format!("{{{{ read_to_str {:?} }}}}", root.path().join("a.txt"));
+        assert_eq!(hbs.render_template(&tmpl, &data)?, "content of a");
+        let tmpl = format!("{{{{ read_to_str {:?} }}}}", root.path().join("b.txt"));
+        assert_eq!(hbs.render_template(&tmpl, &data)?, "");
+        for path in [
+            outside.path().to_path_buf(),
+            root.path().join("../a.txt"),
+            "/file/not/exists".into(),
+        ] {
+            let tmpl = format!("{{{{ read_to_str {:?} }}}}", path);
+            let err = hbs.render_template(&tmpl, &data).unwrap_err();
+            assert!(
+                err.to_string().contains(&*path.to_string_lossy()),
+                "{}",
+                err
+            );
+        }
+        Ok(())
+    }
+
+    /// A tree of files in a temporary directory
+    fn tree(files: &[&str]) -> Result<tempfile::TempDir, Box<dyn Error>> {
+        let root = tempfile::tempdir()?;
+        for file in files {
+            let path = root.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, file)?;
+        }
+        Ok(root)
+    }
+
+    #[test]
+    fn test_read_dir() -> Result<(), Box<dyn Error>> {
+        let root = tree(&["b.txt", "a/c.txt", "c.rs"])?;
+        let dir = root.path().to_string_lossy();
+        assert_renders![
+            (
+                &format!(
+                    r##"{{{{#each (read_dir "{}") }}}}{{{{ name }}}}:{{{{ is_dir }}}}:{{{{ size }}}};{{{{/each}}}}"##,
+                    dir
+                ),
+                &"a:true:*;b.txt:false:5;c.rs:false:4;".replace(
+                    '*',
+                    &std::fs::metadata(root.path().join("a"))?.len().to_string()
+                )
+            ),
+            (
+                &format!(
+                    r##"{{{{#each (read_dir "{}/") }}}}{{{{ path }}}};{{{{/each}}}}"##,
+                    dir
+                ),
+                &format!("{0}/a;{0}/b.txt;{0}/c.rs;", dir)
+            ),
+            (
+                &format!(
+                    r##"{{{{#with (lookup (read_dir "{}") 1) }}}}{{{{ gt modified 0 }}}}{{{{/with}}}}"##,
+                    dir
+                ),
+                "true"
+            ),
+            (r##"{{ len (read_dir "/dir/not/exists") }}"##, "0")
+        ]
+    }
+
+    #[test]
+    fn test_glob() -> Result<(), Box<dyn Error>> {
+        let root = tree(&[
+            "src/lib.rs",
+            "src/b/mod.rs",
+            "src/a/mod.rs",
+            "src/a/data.json",
+            "README.md",
+        ])?;
+        let dir = root.path().to_string_lossy();
+        let glob = |pattern: &str| {
+            format!(
+                r##"{{{{#each (glob "{}/{}") }}}}{{{{ this }}}};{{{{/each}}}}"##,
+                dir, pattern
+            )
+        };
+        let expected = |paths: &[&str]| {
+            paths
+                .iter()
+                .map(|p| format!("{}/{};", dir, p))
+                .collect::<String>()
+        };
+        assert_renders![
+            (
+                &glob("src/**/*.rs"),
+                &expected(&["src/a/mod.rs", "src/b/mod.rs", "src/lib.rs"])
+            ),
+            (&glob("src/*.rs"), &expected(&["src/lib.rs"])),
+            (
+                &glob("src/*/mod.rs"),
+                &expected(&["src/a/mod.rs", "src/b/mod.rs"])
+            ),
+            (&glob("*"), &expected(&["README.md", "src"])),
+            (
+                &glob("**/*.{md,json}"),
+                &expected(&["README.md", "src/a/data.json"])
+            ),
+            (&glob("src/lib.rs"), &expected(&["src/lib.rs"])),
+            (&glob("src/main.rs"), ""),
+            (&glob("not_exists/**/*.rs"), "")
+        ]
+    }
+
+    #[test]
+    fn test_find_files() -> Result<(), Box<dyn Error>> {
+        let root = tree(&[
+            ".git/HEAD",
+            ".gitignore",
+            "Cargo.toml",
+            "src/lib.rs",
+            "src/gen/out.rs",
+            "src/a/.gitignore",
+            "src/a/mod.rs",
+            "src/a/tmp.rs",
+            "target/debug/main.rs",
+        ])?;
+        std::fs::write(root.path().join(".gitignore"), "target/\n/src/gen\n")?;
+        std::fs::write(root.path().join("src/a/.gitignore"), "tmp.rs\n")?;
+        let dir = root.path().to_string_lossy();
+        let find_files = |args: &str| {
+            format!(
+                r##"{{{{#each (find_files "{}" {}) }}}}{{{{ this }}}};{{{{/each}}}}"##,
+                dir, args
+            )
+        };
+        let expected = |paths: &[&str]| {
+            paths
+                .iter()
+                .map(|p| format!("{}/{};", dir, p))
+                .collect::<String>()
+        };
+        assert_renders![
+            (
+                &find_files(""),
+                &expected(&[
+                    ".gitignore",
+                    "Cargo.toml",
+                    "src/a/.gitignore",
+                    "src/a/mod.rs",
+                    "src/lib.rs"
+                ])
+            ),
+            (
+                &find_files(r#"include="*.rs""#),
+                &expected(&["src/a/mod.rs", "src/lib.rs"])
+            ),
+            (
+                &find_files(r#"include=["src/*.rs", "*.toml"]"#),
+                &expected(&["Cargo.toml", "src/lib.rs"])
+            ),
+            (
+                &find_files(r#"include="*.rs" exclude="a""#),
+                &expected(&["src/lib.rs"])
+            ),
+            (
+                &find_files(r#"include="*.rs, *.toml" exclude=["src/lib.rs"]"#),
+                &expected(&["Cargo.toml", "src/a/mod.rs"])
+            ),
+            (
+                &find_files(r#"include="*.rs" gitignore=false"#),
+                &expected(&[
+                    "src/a/mod.rs",
+                    "src/a/tmp.rs",
+                    "src/gen/out.rs",
+                    "src/lib.rs",
+                    "target/debug/main.rs"
+                ])
+            ),
+            (r##"{{ len (find_files "/dir/not/exists") }}"##, "0")
+        ]
+    }
+
+    #[test]
+    fn test_find_files_gitignore_scope() -> Result<(), Box<dyn Error>> {
+        let outer = tree(&[
+            ".gitignore",
+            "project/.git/HEAD",
+            "project/.git/info/exclude",
+            "project/.gitignore",
+            "project/sub/a.txt",
+            "project/sub/b.log",
+            "project/sub/keep.log",
+            "project/sub/c.rs",
+            "project/sub/d.md",
+        ])?;
+        std::fs::write(outer.path().join(".gitignore"), "*.txt\n")?;
+        std::fs::write(
+            outer.path().join("project/.gitignore"),
+            "*.log\n!keep.log\n",
+        )?;
+        std::fs::write(outer.path().join("project/.git/info/exclude"), "*.rs\n")?;
+        let sub = outer.path().join("project/sub").canonicalize()?;
+        let tmpl = format!(
+            r##"{{{{#each (find_files "{}") }}}}{{{{ file_name this }}}};{{{{/each}}}}"##,
+            sub.to_string_lossy()
+        );
+        // without sandbox, only the rules under the directory are read
+        let hbs = crate::new_hbs();
+        assert_eq!(
+            hbs.render_template(&tmpl, &())?,
+            "a.txt;b.log;c.rs;d.md;keep.log;"
+        );
+        // with a sandbox, the rules are read up to its root (not above)
+        let mut hbs = crate::new_hbs();
+        super::register_with_sandbox(
+            &mut hbs,
+            ErrorPolicy::Lenient,
+            Some(Arc::new(FsSandbox::new(outer.path().join("project"))?)),
+        );
+        assert_eq!(hbs.render_template(&tmpl, &())?, "a.txt;d.md;keep.log;");
+        Ok(())
+    }
+
+    #[test]
+    fn test_listing_strict_and_sandbox() -> Result<(), Box<dyn Error>> {
+        let root = tree(&["a.txt"])?;
+        let outside = tree(&["secret.txt"])?;
+        let mut hbs = crate::new_hbs();
+        super::register_with_sandbox(
+            &mut hbs,
+            ErrorPolicy::Strict,
+            Some(Arc::new(FsSandbox::new(root.path())?)),
+        );
+        let data = ();
+        let dir = root.path().to_string_lossy();
+        for tmpl in [
+            r##"{{#each (read_dir "{}") }}{{ this.name }}{{/each}}"##,
+            r##"{{#each (glob "{}/*.txt") }}{{ this }}{{/each}}"##,
+            r##"{{#each (find_files "{}") }}{{ this }}{{/each}}"##,
+        ] {
+            let rendered = hbs.render_template(&tmpl.replace("{}", &dir), &data)?;
+            assert!(rendered.ends_with("a.txt"), "{}: {}", tmpl, rendered);
+            let tmpl = tmpl.replace("{}", &outside.path().to_string_lossy());
+            let err = hbs.render_template(&tmpl, &data).unwrap_err();
+            assert!(err.to_string().contains("sandbox"), "{}: {}", tmpl, err);
+        }
+        for tmpl in [
+            r##"{{ read_dir "{}/not_exists" }}"##,
+            r##"{{ find_files "{}/not_exists" }}"##,
+        ] {
+            let tmpl = tmpl.replace("{}", &dir);
+            let err = hbs.render_template(&tmpl, &data).unwrap_err();
+            assert!(err.to_string().contains("not_exists"), "{}: {}", tmpl, err);
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_to_str_sandbox_symlink_after_parent_dir() -> Result<(), Box<dyn Error>> {
+        let root = tempfile::tempdir()?;
+        let outside = tempfile::tempdir()?;
+        std::fs::write(outside.path().join("secret.txt"), "secret")?;
+        std::os::unix::fs::symlink(outside.path(), root.path().join("link"))?;
+        let mut hbs = crate::new_hbs();
+        super::register_with_sandbox(
+            &mut hbs,
+            ErrorPolicy::Lenient,
+            Some(Arc::new(FsSandbox::new(root.path())?)),
+        );
+        for p in ["nope/../link/secret.txt", "link/secret.txt"] {
+            let tmpl = format!("{{{{ read_to_str {:?} }}}}", root.path().join(p));
+            assert!(hbs.render_template(&tmpl, &()).is_err(), "{}", p);
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_dir_sandbox_symlink() -> Result<(), Box<dyn Error>> {
+        let root = tree(&["a.txt"])?;
+        let outside = tree(&["secret/big.txt"])?;
+        std::os::unix::fs::symlink(outside.path().join("secret"), root.path().join("link"))?;
+        let mut hbs = crate::new_hbs();
+        super::register_with_sandbox(
+            &mut hbs,
+            ErrorPolicy::Lenient,
+            Some(Arc::new(FsSandbox::new(root.path())?)),
+        );
+        let tmpl = format!(
+            r##"{{{{#each (read_dir "{}") }}}}{{{{ name }}}}:{{{{ is_dir }}}};{{{{/each}}}}"##,
+            root.path().to_string_lossy()
+        );
+        // the target of the symlink (outside of the sandbox) is not read
+        assert_eq!(hbs.render_template(&tmpl, &())?, "a.txt:false;link:false;");
+        Ok(())
+    }
 }
@@ -0,0 +1,446 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, JsonValue as Json, RenderContext, RenderError,
+    RenderErrorReason, ScopedJson,
+};
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::encoding_helpers::hex_encode;
+use crate::file_helpers::{read_file, FsSandbox};
+use crate::Capability;
+use crate::ErrorPolicy;
+use crate::HelperRegistry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Sha256,
+    Sha1,
+    Sha512,
+    Md5,
+    Blake3,
+    Crc32,
+}
+
+impl Algorithm {
+    const ALL: [Algorithm; 6] = [
+        Algorithm::Sha256,
+        Algorithm::Sha1,
+        Algorithm::Sha512,
+        Algorithm::Md5,
+        Algorithm::Blake3,
+        Algorithm::Crc32,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha1 => "sha1",
+            Algorithm::Sha512 => "sha512",
+            Algorithm::Md5 => "md5",
+            Algorithm::Blake3 => "blake3",
+            Algorithm::Crc32 => "crc32",
+        }
+    }
+
+    fn file_helper_name(&self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "file_sha256",
+            Algorithm::Sha1 => "file_sha1",
+            Algorithm::Sha512 => "file_sha512",
+            Algorithm::Md5 => "file_md5",
+            Algorithm::Blake3 => "file_blake3",
+            Algorithm::Crc32 => "file_crc32",
+        }
+    }
+}
+
+enum Hasher {
+    Sha256(Sha256),
+    Sha1(Sha1),
+    Sha512(Sha512),
+    Md5(Md5),
+    Blake3(Box<blake3::Hasher>),
+    Crc32(crc32fast::Hasher),
+}
+
+impl Hasher {
+    fn new(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            Algorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+            Algorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+            Algorithm::Md5 => Hasher::Md5(Md5::new()),
+            Algorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+            Algorithm::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Sha1(h) => h.update(data),
+            Hasher::Sha512(h) => h.update(data),
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+            Hasher::Crc32(h) => h.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Sha256(h) => h.finalize().to_vec(),
+            Hasher::Sha1(h) => h.finalize().to_vec(),
+            Hasher::Sha512(h) => h.finalize().to_vec(),
+            Hasher::Md5(h) => h.finalize().to_vec(),
+            Hasher::Blake3(h) => h.finalize().as_bytes().to_vec(),
+            Hasher::Crc32(h) => h.finalize().to_be_bytes().to_vec(),
+        }
+    }
+}
+
+/// Stream the content of the file at `path` into the hasher
+fn digest_file(algorithm: Algorithm, path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher.finalize())
+}
+
+fn hmac_digest(algorithm: &str, key: &str, data: &str) -> Result<Vec<u8>, RenderError> {
+    fn compute<M: Mac + hmac::digest::KeyInit>(key: &str, data: &str) -> Vec<u8> {
+        // `new_from_slice` accepts keys of any size for HMAC
+        let mut mac =
+            <M as Mac>::new_from_slice(key.as_bytes()).expect("HMAC accepts any key size");
+        mac.update(data.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+    match algorithm {
+        "sha256" => Ok(compute::<Hmac<Sha256>>(key, data)),
+        "sha1" => Ok(compute::<Hmac<Sha1>>(key, data)),
+        "sha512" => Ok(compute::<Hmac<Sha512>>(key, data)),
+        "md5" => Ok(compute::<Hmac<Md5>>(key, data)),
+        _ => Err(crate::to_other_error(format!(
+            "hmac: algorithm '{}' unknown (expected: sha256, sha1, sha512, md5)",
+            algorithm
+        ))),
+    }
+}
+
+fn find_str_param<'a>(
+    h: &'a Helper,
+    helper_name: &'static str,
+    idx: usize,
+) -> Result<&'a str, RenderError> {
+    h.param(idx)
+        .and_then(|v| v.value().as_str())
+        .ok_or_else(|| RenderErrorReason::ParamNotFoundForIndex(helper_name, idx).into())
+}
+
+/// Render the digest as hex (default) or base64 according to the `encoding` hash param
+fn encode_digest(h: &Helper, helper_name: &str, digest: &[u8]) -> Result<Json, RenderError> {
+    match h
+        .hash_get("encoding")
+        .and_then(|v| v.value().as_str())
+        .unwrap_or("hex")
+    {
+        "hex" => Ok(Json::String(hex_encode(digest, false))),
+        "base64" => Ok(Json::String(STANDARD.encode(digest))),
+        encoding => Err(crate::to_other_error(format!(
+            "{}: encoding '{}' unknown (expected: hex, base64)",
+            helper_name, encoding
+        ))),
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct digest_fct {
+    algorithm: Algorithm,
+}
+
+impl HelperDef for digest_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let helper_name = self.algorithm.name();
+        let data = find_str_param(h, helper_name, 0)?;
+        let mut hasher = Hasher::new(self.algorithm);
+        hasher.update(data.as_bytes());
+        encode_digest(h, helper_name, &hasher.finalize()).map(ScopedJson::Derived)
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct file_digest_fct {
+    algorithm: Algorithm,
+    policy: ErrorPolicy,
+    sandbox: Option<Arc<FsSandbox>>,
+}
+
+impl HelperDef for file_digest_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let helper_name = self.algorithm.file_helper_name();
+        let path = find_str_param(h, helper_name, 0)?;
+        let digest = read_file(
+            helper_name,
+            path,
+            self.policy,
+            self.sandbox.as_deref(),
+            |p| {
+                digest_file(self.algorithm, p).map(Some).map_err(|e| {
+                    std::io::Error::new(
+                        e.kind(),
+                        format!("{}: failed to read '{}': {}", helper_name, path, e),
+                    )
+                })
+            },
+        )?;
+        match digest {
+            Some(digest) => encode_digest(h, helper_name, &digest).map(ScopedJson::Derived),
+            // missing file with the lenient policy
+            None => Ok(ScopedJson::Derived(Json::String(String::new()))),
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct hmac_fct;
+
+impl HelperDef for hmac_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let key = find_str_param(h, "hmac", 0)?;
+        let data = find_str_param(h, "hmac", 1)?;
+        let algorithm = h
+            .hash_get("algorithm")
+            .and_then(|v| v.value().as_str())
+            .unwrap_or("sha256");
+        encode_digest(h, "hmac", &hmac_digest(algorithm, key, data)?).map(ScopedJson::Derived)
+    }
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "hash", feature: Some("hash");
+    "sha256"(s: "string") [encoding: "string" = "hex"] -> "string" {
+        r##"{{ sha256 "hello" }}"## => "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    }
+    "file_sha256"(path: "string") [encoding: "string" = "hex"] -> "string", requires FsRead {
+        r##"{{ len (file_sha256 "Cargo.toml") }}"## => "64", in_repo
+    }
+    "sha1"(s: "string") [encoding: "string" = "hex"] -> "string" {
+        r##"{{ sha1 "hello" }}"## => "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"
+    }
+    "file_sha1"(path: "string") [encoding: "string" = "hex"] -> "string", requires FsRead {
+        r##"{{ len (file_sha1 "Cargo.toml") }}"## => "40", in_repo
+    }
+    "sha512"(s: "string") [encoding: "string" = "hex"] -> "string" {
+        r##"{{ sha512 "hello" encoding="base64" }}"## => "m3HSJL1i83hdltRq0+o9czGb+8KJDKra4t/3JRlnPKcjI8PZm6XBHXx6zG4UuMXaDEZjR1wuXDre9G9zvN7AQw=="
+    }
+    "file_sha512"(path: "string") [encoding: "string" = "hex"] -> "string", requires FsRead {
+        r##"{{ len (file_sha512 "Cargo.toml") }}"## => "128", in_repo
+    }
+    "md5"(s: "string") [encoding: "string" = "hex"] -> "string" {
+        r##"{{ md5 "hello" }}"## => "5d41402abc4b2a76b9719d911017c592"
+    }
+    "file_md5"(path: "string") [encoding: "string" = "hex"] -> "string", requires FsRead {
+        r##"{{ len (file_md5 "Cargo.toml") }}"## => "32", in_repo
+    }
+    "blake3"(s: "string") [encoding: "string" = "hex"] -> "string" {
+        r##"{{ blake3 "hello" }}"## => "ea8f163db38682925e4491c5e58d4bb3506ef8c14eb78a86e908c5624a67200f"
+    }
+    "file_blake3"(path: "string") [encoding: "string" = "hex"] -> "string", requires FsRead {
+        r##"{{ len (file_blake3 "Cargo.toml") }}"## => "64", in_repo
+    }
+    "crc32"(s: "string") [encoding: "string" = "hex"] -> "string" {
+        r##"{{ crc32 "hello" }}"## => "3610a686"
+    }
+    "file_crc32"(path: "string") [encoding: "string" = "hex"] -> "string", requires FsRead {
+        r##"{{ len (file_crc32 "Cargo.toml") }}"## => "8", in_repo
+    }
+    "hmac"(key: "string", s: "string") [algorithm: "string" = "sha256", encoding: "string" = "hex"] -> "string" {
+        r##"{{ hmac "key" "hello" }}"## => "9307b3b915efb5171ff14d8cb55fbcc798c6c0ef1456d66ded1a6aa723a58b7b"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with_policy(handlebars, ErrorPolicy::default())
+}
+
+pub fn register_with_policy(handlebars: &mut impl HelperRegistry, policy: ErrorPolicy) {
+    register_with_sandbox(handlebars, policy, None)
+}
+
+/// Register the hash helpers, the `file_*` ones are restricted to the paths allowed by `sandbox` (if any)
+pub fn register_with_sandbox(
+    handlebars: &mut impl HelperRegistry,
+    policy: ErrorPolicy,
+    sandbox: Option<Arc<FsSandbox>>,
+) {
+    for algorithm in Algorithm::ALL {
+        handlebars.register_helper(algorithm.name(), Box::new(digest_fct { algorithm }));
+        handlebars.register_helper_requiring(
+            Capability::FsRead,
+            algorithm.file_helper_name(),
+            Box::new(file_digest_fct {
+                algorithm,
+                policy,
+                sandbox: sandbox.clone(),
+            }),
+        );
+    }
+    handlebars.register_helper("hmac", Box::new(hmac_fct));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_renders;
+    use crate::ErrorPolicy;
+    use std::error::Error;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_digest() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ sha512 "hello" }}"##,
+                r##"9b71d224bd62f3785d96d46ad3ea3d73319bfbc2890caadae2dff72519673ca72323c3d99ba5c11d7c7acc6e14b8c5da0c4663475c2e5c3adef46f73bcdec043"##
+            ),
+            (
+                r##"{{ sha256 "" }}"##,
+                r##"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"##
+            ),
+            (
+                r##"{{ sha256 "hello" encoding="base64" }}"##,
+                r##"LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ="##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_file_digest() -> Result<(), Box<dyn Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(file, "hello")?;
+        let path = format!("{:?}", file.path());
+        assert_renders![
+            (
+                &format!("{{{{ file_sha256 {} }}}}", path),
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+            ),
+            (
+                &format!("{{{{ file_md5 {} }}}}", path),
+                "5d41402abc4b2a76b9719d911017c592"
+            ),
+            (&format!("{{{{ file_crc32 {} }}}}", path), "3610a686"),
+            (
+                &format!("{{{{ file_sha1 {} encoding=\"base64\" }}}}", path),
+                "qvTGHdzF6KLavt4PO0gs2a6pQ00="
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_hmac() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ hmac "key" "The quick brown fox jumps over the lazy dog" }}"##,
+                r##"f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"##
+            ),
+            (
+                r##"{{ hmac "key" "The quick brown fox jumps over the lazy dog" algorithm="sha1" }}"##,
+                r##"de7c9b85b8b78aa6bc8a7a36f70a90701c9db4d9"##
+            ),
+            (
+                r##"{{ hmac "key" "The quick brown fox jumps over the lazy dog" algorithm="md5" }}"##,
+                r##"80070713463e7749b90c2dc24911e275"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let hbs = crate::new_hbs();
+        let data = ();
+        for tmpl in [
+            r##"{{ sha256 }}"##,
+            r##"{{ sha256 42 }}"##,
+            r##"{{ sha256 "hello" encoding="base32" }}"##,
+            r##"{{ hmac "key" "data" algorithm="blake3" }}"##,
+        ] {
+            assert!(hbs.render_template(tmpl, &data).is_err(), "{}", tmpl);
+        }
+    }
+
+    #[test]
+    fn test_file_digest_missing() -> Result<(), Box<dyn Error>> {
+        let mut hbs = handlebars::Handlebars::new();
+        super::register_with_policy(&mut hbs, ErrorPolicy::Strict);
+        let err = hbs
+            .render_template(r##"{{ file_sha256 "/file/not/exists" }}"##, &())
+            .unwrap_err();
+        assert!(err.to_string().contains("/file/not/exists"), "{}", err);
+        assert_renders![
+            (r##"{{ file_sha256 "/file/not/exists" }}"##, ""),
+            (
+                r##"{{ file_crc32 "/file/not/exists" encoding="base64" }}"##,
+                ""
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_file_digest_sandbox() -> Result<(), Box<dyn Error>> {
+        let root = tempfile::tempdir()?;
+        std::fs::write(root.path().join("a.txt"), "hello")?;
+        let outside = NamedTempFile::new()?;
+        let mut hbs = crate::new_hbs();
+        crate::register_with_sandbox(
+            &mut hbs,
+            crate::ErrorPolicy::Lenient,
+            crate::FsSandbox::new(root.path())?,
+        );
+        let data = ();
+        let tmpl = format!("{{{{ file_crc32 {:?} }}}}", root.path().join("a.txt"));
+        assert_eq!(hbs.render_template(&tmpl, &data)?, "3610a686");
+        for tmpl in [
+            format!("{{{{ file_crc32 {:?} }}}}", outside.path()),
+            format!("{{{{ base64_file {:?} }}}}", outside.path()),
+            format!("{{{{ read_to_str {:?} }}}}", root.path().join("../a.txt")),
+        ] {
+            assert!(hbs.render_template(&tmpl, &data).is_err(), "{}", tmpl);
+        }
+        Ok(())
+    }
+}
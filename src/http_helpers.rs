@@ -1,9 +1,13 @@
 #[cfg(feature = "http_attohttpc")]
 use attohttpc;
-use handlebars::{handlebars_helper, Handlebars};
+use handlebars::handlebars_helper;
 #[cfg(all(feature = "http_reqwest", not(feature = "http_attohttpc")))]
 use reqwest;
 
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::Capability;
+use crate::HelperRegistry;
+
 #[cfg(feature = "http_attohttpc")]
 fn http_get_fct<T: AsRef<str>>(url: T) -> Result<String, attohttpc::Error> {
     attohttpc::get(url.as_ref()).send()?.text()
@@ -14,15 +18,34 @@ fn http_get_fct<T: AsRef<str>>(url: T) -> Result<String, reqwest::Error> {
     reqwest::blocking::get(url.as_ref())?.text()
 }
 
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "http",
+    feature: if cfg!(feature = "http_attohttpc") {
+        Some("http_attohttpc")
+    } else {
+        Some("http_reqwest")
+    };
+    "http_get"(url: "string") -> "string", requires Network {
+        r##"{{ http_get "https://example.com" }}"## => "<!doctype html>..."
+    }
+    "gitignore_io"(templates: "string") -> "string", requires Network {
+        r##"{{ gitignore_io "rust" }}"## => "# Created by https://www.toptal.com/developers/gitignore/api/rust..."
+    }
+};
+
 #[cfg(any(feature = "http_reqwest", feature = "http_attohttpc"))]
-pub fn register(handlebars: &mut Handlebars) {
+pub fn register(handlebars: &mut impl HelperRegistry) {
     {
         handlebars_helper!(http_get: |v: str| http_get_fct(v).map_err(crate::to_nested_error)?);
-        handlebars.register_helper("http_get", Box::new(http_get))
+        handlebars.register_helper_requiring(Capability::Network, "http_get", Box::new(http_get))
     }
     {
         handlebars_helper!(gitignore_io: |v: str| http_get_fct(format!("https://www.gitignore.io/api/{}", v)).map_err(crate::to_nested_error)?);
-        handlebars.register_helper("gitignore_io", Box::new(gitignore_io))
+        handlebars.register_helper_requiring(
+            Capability::Network,
+            "gitignore_io",
+            Box::new(gitignore_io),
+        )
     }
 }
 
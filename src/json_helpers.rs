@@ -1,4 +1,6 @@
+use crate::catalog::{helpers_info, HelperInfo};
 use crate::outputs::StringOutput;
+use crate::HelperRegistry;
 use handlebars::{
     handlebars_helper, Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext,
     RenderError, Renderable, ScopedJson,
@@ -317,7 +319,29 @@ fn to_json_block<'reg, 'rc>(
 
 handlebars_helper!(json_query_fct: |expr: str, data: Json| json_query(expr, data).map_err(crate::to_nested_error)?);
 
-pub fn register(handlebars: &mut Handlebars) {
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "json", feature: Some("json");
+    "json_to_str"(data: "any") [format: "string" = "json"] -> "string" {
+        r##"{{ json_to_str {"foo": {"bar": true}} format="yaml" }}"## => "foo:\n  bar: true\n"
+    }
+    "str_to_json"(data: "string") [format: "string" = "json"] -> "any" {
+        r##"{{ lookup (str_to_json "foo = 42" format="toml") "foo" }}"## => "42"
+    }
+    "from_json"() [format: "string" = "json"] -> "block" {
+        r##"{{#from_json format="toml"}}{"foo": {"bar": true}}{{/from_json}}"## => "[foo]\nbar = true\n"
+    }
+    "to_json"() [format: "string" = "json"] -> "block" {
+        r##"{{#to_json format="yaml"}}foo: 42{{/to_json}}"## => "{\n  \"foo\": 42\n}"
+    }
+    "json_query"(query: "string", data: "any") -> "any" {
+        r##"{{ json_query "foo.bar" {"foo": {"bar": 42}} }}"## => "42"
+    }
+    "json_str_query"(query: "string", data: "string") [format: "string" = "json"] -> "string" {
+        r##"{{ json_str_query "foo.bar" "{\"foo\": {\"bar\": 42}}" }}"## => "42"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
     handlebars.register_helper("json_to_str", Box::new(json_to_str_fct));
     handlebars.register_helper("str_to_json", Box::new(str_to_json_fct));
     handlebars.register_helper("from_json", Box::new(from_json_block));
@@ -1,6 +1,13 @@
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::file_helpers::FsSandbox;
 use crate::outputs::StringOutput;
-use handlebars::{Context, Handlebars, Helper, HelperResult, Output, RenderContext, Renderable};
+use crate::HelperRegistry;
+use crate::{Capabilities, Capability};
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, Renderable,
+};
 use jsonnet::JsonnetVm;
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -9,13 +16,36 @@ enum JsonnetError {
     EvaluateError { source_str: String },
 }
 
+/// `{{#jsonnet}}...{{/jsonnet}}`, the `import` of files requires the capability `fs_read`
+/// and is restricted to the paths allowed by `sandbox` (if any)
+#[allow(non_camel_case_types)]
+struct jsonnet_block {
+    fs_read: bool,
+    sandbox: Option<Arc<FsSandbox>>,
+}
+
+impl HelperDef for jsonnet_block {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        jsonnet_render(h, r, ctx, rc, out, self.fs_read, self.sandbox.clone())
+    }
+}
+
 #[allow(clippy::let_and_return)]
-fn jsonnet_block<'reg, 'rc>(
+fn jsonnet_render<'reg, 'rc>(
     h: &Helper<'rc>,
     r: &'reg Handlebars,
     ctx: &'rc Context,
     rc: &mut RenderContext<'reg, 'rc>,
     out: &mut dyn Output,
+    fs_read: bool,
+    sandbox: Option<Arc<FsSandbox>>,
 ) -> HelperResult {
     let mut content = StringOutput::default();
     h.template()
@@ -26,6 +56,21 @@ fn jsonnet_block<'reg, 'rc>(
         input
     } else {
         let mut vm = JsonnetVm::new();
+        if !fs_read {
+            vm.import_callback(|_, _, rel| {
+                Err(Capability::FsRead.denied(&format!("import of '{}'", rel.display())))
+            });
+        } else if let Some(sandbox) = sandbox {
+            vm.import_callback(move |_, base, rel| {
+                let path = base.join(rel);
+                let resolved = sandbox
+                    .check(&path.to_string_lossy())
+                    .map_err(|e| format!("import denied: {}", e))?;
+                let content = std::fs::read_to_string(&resolved)
+                    .map_err(|e| format!("import of '{}' failed: {}", path.display(), e))?;
+                Ok((path, content))
+            });
+        }
         vm.string_output(
             h.hash_get("string_output")
                 .and_then(|v| v.value().as_bool())
@@ -50,16 +95,44 @@ fn jsonnet_block<'reg, 'rc>(
     out.write(&res).map_err(crate::to_nested_error)
 }
 
-pub fn register(handlebars: &mut Handlebars) {
-    handlebars.register_helper("jsonnet", Box::new(jsonnet_block));
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "jsonnet", feature: Some("jsonnet");
+    "jsonnet"() [string_output: "boolean" = "false"] -> "block" {
+        r##"{{#jsonnet}}{ "foo": 1 + 1 }{{/jsonnet}}"## => "{\n   \"foo\": 2\n}\n"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with(handlebars, None, Capabilities::default())
+}
+
+/// Register the jsonnet helper, the `import` of files is restricted to the paths allowed by
+/// `sandbox` (if any), and fails without the `fs_read` capability
+pub fn register_with(
+    handlebars: &mut impl HelperRegistry,
+    sandbox: Option<Arc<FsSandbox>>,
+    capabilities: Capabilities,
+) {
+    handlebars.register_helper(
+        "jsonnet",
+        Box::new(jsonnet_block {
+            fs_read: capabilities.grants(Capability::FsRead),
+            sandbox,
+        }),
+    );
 }
 
 #[cfg(test)]
 mod tests {
     //use super::*;
     use crate::assert_renders;
+    use crate::file_helpers::FsSandbox;
     use crate::tests::normalize_nl;
+    use crate::Capabilities;
     use std::error::Error;
+    use std::io::Write;
+    use std::path::Path;
+    use std::sync::Arc;
 
     #[test]
     fn test_jsonnet_block() -> Result<(), Box<dyn Error>> {
@@ -166,4 +239,62 @@ mod tests {
             ),
         ]
     }
+
+    #[test]
+    fn test_jsonnet_import_capability() -> Result<(), Box<dyn Error>> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        write!(file, "hello")?;
+        let tmpl = format!(
+            "{{{{#jsonnet string_output=true}}}}importstr {:?}{{{{/jsonnet}}}}",
+            file.path()
+        );
+        let mut hbs = handlebars::Handlebars::new();
+        super::register_with(&mut hbs, None, Capabilities::all());
+        assert_eq!(hbs.render_template(&tmpl, &())?, "hello\n");
+
+        let mut hbs = handlebars::Handlebars::new();
+        super::register_with(
+            &mut hbs,
+            None,
+            Capabilities {
+                fs_read: false,
+                ..Capabilities::all()
+            },
+        );
+        let err = hbs.render_template(&tmpl, &()).unwrap_err();
+        assert!(err.to_string().contains("fs_read"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn test_jsonnet_import_sandbox() -> Result<(), Box<dyn Error>> {
+        let root = tempfile::tempdir()?;
+        std::fs::write(root.path().join("inside.txt"), "inside")?;
+        let mut outside = tempfile::NamedTempFile::new()?;
+        write!(outside, "outside")?;
+        let mut hbs = handlebars::Handlebars::new();
+        super::register_with(
+            &mut hbs,
+            Some(Arc::new(FsSandbox::new(root.path())?)),
+            Capabilities::all(),
+        );
+        let tmpl = |path: &Path| {
+            format!(
+                "{{{{#jsonnet string_output=true}}}}importstr {:?}{{{{/jsonnet}}}}",
+                path
+            )
+        };
+        assert_eq!(
+            hbs.render_template(&tmpl(&root.path().join("inside.txt")), &())?,
+            "inside\n"
+        );
+        for path in [
+            outside.path().to_path_buf(),
+            root.path().join("../outside.txt"),
+        ] {
+            let err = hbs.render_template(&tmpl(&path), &()).unwrap_err();
+            assert!(err.to_string().contains("sandbox"), "{}", err);
+        }
+        Ok(())
+    }
 }
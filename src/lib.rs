@@ -8,18 +8,34 @@
 // )]
 
 use handlebars::no_escape;
-use handlebars::Handlebars;
+use handlebars::{Handlebars, HelperDef};
+use std::sync::Arc;
 
 #[cfg(feature = "jsontype")]
 pub mod assign_helpers;
+pub mod catalog;
+#[cfg(feature = "collection")]
+pub mod collection_helpers;
+#[cfg(feature = "datetime")]
+pub mod datetime_helpers;
+#[cfg(feature = "encoding")]
+pub mod encoding_helpers;
 pub mod env_helpers;
+#[cfg(feature = "string")]
+pub mod escape_helpers;
 pub mod file_helpers;
+#[cfg(feature = "hash")]
+pub mod hash_helpers;
 #[cfg(any(feature = "http_reqwest", feature = "http_attohttpc"))]
 pub mod http_helpers;
 #[cfg(feature = "json")]
 pub mod json_helpers;
 #[cfg(feature = "jsonnet")]
 pub mod jsonnet_helpers;
+#[cfg(feature = "logic")]
+pub mod logic_helpers;
+#[cfg(feature = "math")]
+pub mod math_helpers;
 pub mod outputs;
 pub mod path_helpers;
 #[cfg(feature = "regex")]
@@ -31,6 +47,451 @@ pub mod string_helpers;
 #[cfg(feature = "uuid")]
 pub mod uuid_helpers;
 
+pub use catalog::{catalog, HelperInfo, Param};
+pub use file_helpers::FsSandbox;
+
+/// How the helpers react to a missing file, an undefined environment variable, a region not found,
+/// an invalid input that could be returned as is,...
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Log (warn or info) and render a fallback value (empty string, the input,...)
+    #[default]
+    Lenient,
+    /// Fail the rendering with a `RenderError`
+    Strict,
+}
+
+impl ErrorPolicy {
+    /// Return `fallback` (after logging `msg` at `level`) if lenient, else an error with `msg`
+    pub(crate) fn recover<T>(
+        self,
+        level: log::Level,
+        fallback: T,
+        msg: String,
+    ) -> Result<T, handlebars::RenderError> {
+        match self {
+            ErrorPolicy::Lenient => {
+                log::log!(level, "{}", msg);
+                Ok(fallback)
+            }
+            ErrorPolicy::Strict => Err(to_other_error(msg)),
+        }
+    }
+}
+
+/// Access to the outside world granted to the helpers (everything by default).
+///
+/// The helpers that require a denied capability stay registered but always fail with an error.
+///
+/// ```rust
+/// use handlebars_misc_helpers::{register_with, Capabilities};
+///
+/// let mut handlebars = handlebars::Handlebars::new();
+/// register_with(
+///     &mut handlebars,
+///     Capabilities {
+///         network: false,
+///         env: false,
+///         ..Capabilities::all()
+///     },
+/// );
+/// assert!(handlebars.render_template(r#"{{ env_var "HOME" }}"#, &()).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `http_get`, `gitignore_io`
+    pub network: bool,
+    /// `read_to_str`, `read_dir`, `glob`, `find_files`, `base64_file`, `file_sha256` & co,
+    /// `canonicalize`, `import` in `jsonnet`
+    /// (`parent`, `file_name`, `extension` don't resolve the path if denied)
+    pub fs_read: bool,
+    /// helpers that write files (none for now)
+    pub fs_write: bool,
+    /// `env_var` (`now` doesn't read `SOURCE_DATE_EPOCH`, `find_files` doesn't read the global
+    /// excludes file of git if denied)
+    pub env: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl Capabilities {
+    pub fn all() -> Self {
+        Self {
+            network: true,
+            fs_read: true,
+            fs_write: true,
+            env: true,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            network: false,
+            fs_read: false,
+            fs_write: false,
+            env: false,
+        }
+    }
+
+    pub fn grants(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Network => self.network,
+            Capability::FsRead => self.fs_read,
+            Capability::FsWrite => self.fs_write,
+            Capability::Env => self.env,
+        }
+    }
+
+    /// `def` if `capability` is granted, else a helper that always fails
+    fn restrict(
+        &self,
+        capability: Capability,
+        helper_name: &str,
+        def: Box<dyn HelperDef + Send + Sync + 'static>,
+    ) -> Box<dyn HelperDef + Send + Sync + 'static> {
+        if self.grants(capability) {
+            def
+        } else {
+            Box::new(denied_fct {
+                helper_name: helper_name.to_owned(),
+                capability,
+            })
+        }
+    }
+}
+
+/// An access to the outside world, required by some helpers (see [`Capabilities`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    Network,
+    FsRead,
+    FsWrite,
+    Env,
+}
+
+impl Capability {
+    /// Name of the field of [`Capabilities`] (eg `fs_read`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::Network => "network",
+            Capability::FsRead => "fs_read",
+            Capability::FsWrite => "fs_write",
+            Capability::Env => "env",
+        }
+    }
+
+    /// The message of the error when `what` requires this capability and it's denied
+    pub(crate) fn denied(&self, what: &str) -> String {
+        format!("{} denied, the capability '{}' is disabled", what, self)
+    }
+}
+
+impl std::fmt::Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Replace a helper that requires a denied capability
+#[allow(non_camel_case_types)]
+struct denied_fct {
+    helper_name: String,
+    capability: Capability,
+}
+
+impl HelperDef for denied_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        _: &handlebars::Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc handlebars::Context,
+        _: &mut handlebars::RenderContext<'reg, 'rc>,
+    ) -> Result<handlebars::ScopedJson<'reg>, handlebars::RenderError> {
+        Err(to_other_error(
+            self.capability
+                .denied(&format!("helper: {}", self.helper_name)),
+        ))
+    }
+}
+
+/// Where the helpers are registered: a `Handlebars` registry, or the registry configured by
+/// [`HelpersConfig`] (to filter and to rename the helpers)
+pub trait HelperRegistry {
+    fn register_helper(&mut self, name: &str, def: Box<dyn HelperDef + Send + Sync + 'static>);
+
+    /// Register a helper that requires `capability`, registered as is by default
+    /// ([`HelpersConfig`] replaces it by a failing helper if the capability is denied)
+    fn register_helper_requiring(
+        &mut self,
+        _capability: Capability,
+        name: &str,
+        def: Box<dyn HelperDef + Send + Sync + 'static>,
+    ) {
+        self.register_helper(name, def)
+    }
+}
+
+impl HelperRegistry for Handlebars<'_> {
+    fn register_helper(&mut self, name: &str, def: Box<dyn HelperDef + Send + Sync + 'static>) {
+        Handlebars::register_helper(self, name, def)
+    }
+}
+
+/// Select, rename and configure the helpers to register into an existing `Handlebars`
+/// (its strict mode and its escape function are not changed, unlike `setup_handlebars`).
+///
+/// The helpers are selected by name or by group (the module of the helper: `string`, `escape`,
+/// `http`, `path`, `env`, `json`, `jsonnet`, `assign`, `file`, `encoding`, `hash`, `region`,
+/// `regex`, `uuid`, `datetime`, `math`, `logic`, `collection`).
+///
+/// ```rust
+/// use handlebars_misc_helpers::HelpersConfig;
+///
+/// let mut handlebars = handlebars::Handlebars::new();
+/// HelpersConfig::new()
+///     .include(["path", "env_var"])
+///     .exclude(["canonicalize"])
+///     .prefix("misc_")
+///     .register(&mut handlebars);
+/// let res = handlebars.render_template(r#"{{ misc_file_name "bar/foo.txt" }}"#, &())?;
+/// assert_eq!(res, "foo.txt");
+/// assert!(handlebars.render_template(r#"{{ misc_canonicalize "." }}"#, &()).is_err());
+/// assert!(handlebars.render_template(r#"{{ file_name "bar/foo.txt" }}"#, &()).is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct HelpersConfig {
+    policy: ErrorPolicy,
+    sandbox: Option<Arc<FsSandbox>>,
+    capabilities: Capabilities,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    prefix: String,
+    environment: Option<Arc<dyn env_helpers::Environment>>,
+    #[cfg(feature = "string")]
+    inflections: Option<Arc<string_helpers::Inflections>>,
+    #[cfg(feature = "regex")]
+    regex_cache: Option<Arc<regex_helpers::RegexCache>>,
+    #[cfg(feature = "datetime")]
+    clock: Option<Arc<dyn datetime_helpers::Clock>>,
+}
+
+impl std::fmt::Debug for HelpersConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HelpersConfig")
+            .field("policy", &self.policy)
+            .field("sandbox", &self.sandbox)
+            .field("capabilities", &self.capabilities)
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HelpersConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Behavior of the helpers on missing or invalid input (default: lenient)
+    pub fn policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Restrict the paths accessed by the helpers (default: no restriction)
+    pub fn sandbox(mut self, sandbox: FsSandbox) -> Self {
+        self.sandbox = Some(Arc::new(sandbox));
+        self
+    }
+
+    /// Access to the outside world granted to the helpers (default: everything)
+    pub fn capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Register only the helpers with these names or in these groups (default: all)
+    pub fn include<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.include.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Don't register the helpers with these names or in these groups
+    pub fn exclude<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Prefix the name of the helpers (eg `misc_` to register `trim` as `misc_trim`)
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_owned();
+        self
+    }
+
+    /// Environment variables read by `env_var` and by `now` (`SOURCE_DATE_EPOCH`),
+    /// if the `env` capability is granted (default: the environment of the process)
+    pub fn environment(mut self, environment: Arc<dyn env_helpers::Environment>) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Custom inflection rules of the string helpers (default: the rules of cruet only)
+    #[cfg(feature = "string")]
+    pub fn inflections(mut self, inflections: Arc<string_helpers::Inflections>) -> Self {
+        self.inflections = Some(inflections);
+        self
+    }
+
+    /// Cache (and named patterns) of the regex helpers (default: a new cache)
+    #[cfg(feature = "regex")]
+    pub fn regex_cache(mut self, cache: Arc<regex_helpers::RegexCache>) -> Self {
+        self.regex_cache = Some(cache);
+        self
+    }
+
+    /// Source of `now` (default: `SOURCE_DATE_EPOCH` if defined and the `env` capability is
+    /// granted, else the system clock)
+    #[cfg(feature = "datetime")]
+    pub fn clock(mut self, clock: Arc<dyn datetime_helpers::Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    fn is_selected(&self, group: &str, name: &str) -> bool {
+        let matches = |names: &[String]| names.iter().any(|n| n == group || n == name);
+        (self.include.is_empty() || matches(&self.include)) && !matches(&self.exclude)
+    }
+
+    /// Register the selected helpers into `handlebars` (or any other [`HelperRegistry`])
+    pub fn register(&self, handlebars: &mut impl HelperRegistry) {
+        let mut registry = ConfiguredRegistry {
+            handlebars,
+            config: self,
+            group: "",
+        };
+        let policy = self.policy;
+        let sandbox = &self.sandbox;
+        let capabilities = self.capabilities;
+        let environment = self
+            .environment
+            .clone()
+            .unwrap_or_else(|| Arc::new(env_helpers::ProcessEnvironment));
+        #[cfg(feature = "string")]
+        string_helpers::register_with(
+            registry.group("string"),
+            self.inflections.clone().unwrap_or_default(),
+            policy,
+        );
+        #[cfg(feature = "string")]
+        escape_helpers::register(registry.group("escape"));
+        #[cfg(any(feature = "http_reqwest", feature = "http_attohttpc"))]
+        http_helpers::register(registry.group("http"));
+        path_helpers::register_with(registry.group("path"), sandbox.clone(), capabilities);
+        env_helpers::register_with_environment(registry.group("env"), policy, environment.clone());
+        #[cfg(feature = "json")]
+        json_helpers::register(registry.group("json"));
+        #[cfg(feature = "jsonnet")]
+        jsonnet_helpers::register_with(registry.group("jsonnet"), sandbox.clone(), capabilities);
+        #[cfg(feature = "jsontype")]
+        assign_helpers::register(registry.group("assign"));
+        file_helpers::register_with(
+            registry.group("file"),
+            policy,
+            sandbox.clone(),
+            capabilities,
+        );
+        #[cfg(feature = "encoding")]
+        encoding_helpers::register_with_sandbox(
+            registry.group("encoding"),
+            policy,
+            sandbox.clone(),
+        );
+        #[cfg(feature = "hash")]
+        hash_helpers::register_with_sandbox(registry.group("hash"), policy, sandbox.clone());
+        #[cfg(feature = "jsontype")]
+        region_helpers::register_with_policy(registry.group("region"), policy);
+        #[cfg(feature = "regex")]
+        regex_helpers::register_with_cache(
+            registry.group("regex"),
+            self.regex_cache.clone().unwrap_or_default(),
+        );
+        #[cfg(feature = "uuid")]
+        uuid_helpers::register(registry.group("uuid"));
+        #[cfg(feature = "datetime")]
+        datetime_helpers::register_with_clock(
+            registry.group("datetime"),
+            // `SOURCE_DATE_EPOCH` is not read without the `env` capability
+            match &self.clock {
+                Some(clock) => clock.clone(),
+                None if capabilities.grants(Capability::Env) => {
+                    datetime_helpers::clock_from_environment(environment.as_ref())
+                }
+                None => Arc::new(datetime_helpers::SystemClock),
+            },
+        );
+        #[cfg(feature = "math")]
+        math_helpers::register(registry.group("math"));
+        #[cfg(feature = "logic")]
+        logic_helpers::register(registry.group("logic"));
+        #[cfg(feature = "collection")]
+        collection_helpers::register(registry.group("collection"));
+    }
+}
+
+/// Register the helpers selected by the config, with their prefixed name,
+/// the helpers requiring a denied capability are replaced
+struct ConfiguredRegistry<'a, R> {
+    handlebars: &'a mut R,
+    config: &'a HelpersConfig,
+    group: &'static str,
+}
+
+impl<R: HelperRegistry> ConfiguredRegistry<'_, R> {
+    fn group(&mut self, group: &'static str) -> &mut Self {
+        self.group = group;
+        self
+    }
+}
+
+impl<R: HelperRegistry> HelperRegistry for ConfiguredRegistry<'_, R> {
+    fn register_helper(&mut self, name: &str, def: Box<dyn HelperDef + Send + Sync + 'static>) {
+        if !self.config.is_selected(self.group, name) {
+            return;
+        }
+        let name = format!("{}{}", self.config.prefix, name);
+        self.handlebars.register_helper(&name, def)
+    }
+
+    fn register_helper_requiring(
+        &mut self,
+        capability: Capability,
+        name: &str,
+        def: Box<dyn HelperDef + Send + Sync + 'static>,
+    ) {
+        if !self.config.is_selected(self.group, name) {
+            return;
+        }
+        let def = self.config.capabilities.restrict(capability, name, def);
+        let name = format!("{}{}", self.config.prefix, name);
+        self.handlebars
+            .register_helper_requiring(capability, &name, def)
+    }
+}
+
 pub fn new_hbs<'reg>() -> Handlebars<'reg> {
     let mut handlebars = Handlebars::new();
     setup_handlebars(&mut handlebars);
@@ -38,31 +499,39 @@ pub fn new_hbs<'reg>() -> Handlebars<'reg> {
 }
 
 pub fn setup_handlebars(handlebars: &mut Handlebars) {
+    setup_handlebars_with_policy(handlebars, ErrorPolicy::default());
+}
+
+pub fn setup_handlebars_with_policy(handlebars: &mut Handlebars, policy: ErrorPolicy) {
     handlebars.set_strict_mode(true);
     handlebars.register_escape_fn(no_escape); //html escaping is the default and cause issue
-    register(handlebars);
+    register_with_policy(handlebars, policy);
 }
 
 pub fn register(handlebars: &mut Handlebars) {
-    #[cfg(feature = "string")]
-    string_helpers::register(handlebars);
-    #[cfg(any(feature = "http_reqwest", feature = "http_attohttpc"))]
-    http_helpers::register(handlebars);
-    path_helpers::register(handlebars);
-    env_helpers::register(handlebars);
-    #[cfg(feature = "json")]
-    json_helpers::register(handlebars);
-    #[cfg(feature = "jsonnet")]
-    jsonnet_helpers::register(handlebars);
-    #[cfg(feature = "jsontype")]
-    assign_helpers::register(handlebars);
-    file_helpers::register(handlebars);
-    #[cfg(feature = "jsontype")]
-    region_helpers::register(handlebars);
-    #[cfg(feature = "regex")]
-    regex_helpers::register(handlebars);
-    #[cfg(feature = "uuid")]
-    uuid_helpers::register(handlebars);
+    HelpersConfig::new().register(handlebars);
+}
+
+/// Register all the helpers, `policy` defines their behavior on missing or invalid input
+pub fn register_with_policy(handlebars: &mut Handlebars, policy: ErrorPolicy) {
+    HelpersConfig::new().policy(policy).register(handlebars);
+}
+
+/// Register all the helpers, the helpers that access the file system (`read_to_str`, `base64_file`,
+/// `file_sha256`, `canonicalize`,...) are restricted to the paths allowed by `sandbox`
+pub fn register_with_sandbox(handlebars: &mut Handlebars, policy: ErrorPolicy, sandbox: FsSandbox) {
+    HelpersConfig::new()
+        .policy(policy)
+        .sandbox(sandbox)
+        .register(handlebars);
+}
+
+/// Register all the helpers, the helpers that require a capability not granted by `capabilities`
+/// (eg `http_get` without `network`) fail with an error
+pub fn register_with(handlebars: &mut Handlebars, capabilities: Capabilities) {
+    HelpersConfig::new()
+        .capabilities(capabilities)
+        .register(handlebars);
 }
 
 #[allow(dead_code)]
@@ -154,23 +623,223 @@ mod tests {
         Ok(())
     }
 
+    /// A registry like `new_hbs`, with the environment variables `vars` (instead of the
+    /// environment of the process, shared by the tests running in parallel)
+    fn new_hbs_with_env(vars: &[(&str, &str)], capabilities: Capabilities) -> Handlebars<'static> {
+        let env: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let mut hbs = Handlebars::new();
+        hbs.set_strict_mode(true);
+        hbs.register_escape_fn(no_escape);
+        HelpersConfig::new()
+            .capabilities(capabilities)
+            .environment(Arc::new(env))
+            .register(&mut hbs);
+        hbs
+    }
+
     #[test]
     #[cfg(all(feature = "string", feature = "json"))]
     fn test_chain_of_default() -> Result<(), Box<dyn Error>> {
-        std::env::set_var("MY_VERSION_1", "1.0.0");
-        assert_renders![
+        let hbs = new_hbs_with_env(&[("MY_VERSION_1", "1.0.0")], Capabilities::all());
+        for (tmpl, expected) in [
             (
                 r##"{{ first_non_empty (env_var "MY_VERSION") "0.0.0" }}"##,
-                r##"0.0.0"##
+                r##"0.0.0"##,
             ),
             (
                 r##"{{ first_non_empty (env_var "MY_VERSION_1") "0.0.0" }}"##,
-                r##"1.0.0"##
+                r##"1.0.0"##,
             ),
             (
                 r##"{{ first_non_empty (unquote (json_str_query "package.edition" (read_to_str "Cargo.toml") format="toml")) (env_var "MY_VERSION") "0.0.0" }}"##,
-                r##"2021"##
+                r##"2021"##,
             ),
-        ]
+        ] {
+            assert_eq!(hbs.render_template(tmpl, &())?, expected, "{}", tmpl);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_capabilities() -> Result<(), Box<dyn Error>> {
+        let vars = [("KEY_CAPABILITIES", "VALUE")];
+        let file = tempfile::NamedTempFile::new()?;
+        let read_tmpl = format!("{{{{ read_to_str {:?} }}}}", file.path());
+        let hbs = new_hbs_with_env(&vars, Capabilities::all());
+        assert_eq!(
+            hbs.render_template(r#"{{ env_var "KEY_CAPABILITIES" }}"#, &())?,
+            "VALUE"
+        );
+        assert_eq!(hbs.render_template(&read_tmpl, &())?, "");
+
+        let hbs = new_hbs_with_env(&vars, Capabilities::none());
+        for tmpl in [
+            r#"{{ env_var "KEY_CAPABILITIES" }}"#,
+            r#"{{ env_var "KEY_CAPABILITIES" default="foo" }}"#,
+            r#"{{ canonicalize "." }}"#,
+            &read_tmpl,
+        ] {
+            let err = hbs.render_template(tmpl, &()).unwrap_err();
+            assert!(err.to_string().contains("denied"), "{}: {}", tmpl, err);
+        }
+        assert_eq!(hbs.render_template(r#"{{ file_name "." }}"#, &())?, "");
+        assert_eq!(
+            hbs.render_template(r#"{{ extension "foo.txt" }}"#, &())?,
+            "txt"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn test_capabilities_clock() -> Result<(), Box<dyn Error>> {
+        let vars = [("SOURCE_DATE_EPOCH", "4102444800")];
+        let tmpl = r#"{{ now format="%Y" }}"#;
+        let hbs = new_hbs_with_env(&vars, Capabilities::all());
+        assert_eq!(hbs.render_template(tmpl, &())?, "2100");
+        let hbs = new_hbs_with_env(
+            &vars,
+            Capabilities {
+                env: false,
+                ..Capabilities::all()
+            },
+        );
+        assert_ne!(hbs.render_template(tmpl, &())?, "2100");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "hash",
+        any(feature = "http_reqwest", feature = "http_attohttpc")
+    ))]
+    fn test_capabilities_network_fs_read() -> Result<(), Box<dyn Error>> {
+        let mut hbs = Handlebars::new();
+        register_with(
+            &mut hbs,
+            Capabilities {
+                network: false,
+                fs_read: false,
+                ..Capabilities::all()
+            },
+        );
+        for (tmpl, capability) in [
+            (r#"{{ http_get "https://example.com" }}"#, "network"),
+            (r#"{{ gitignore_io "rust" }}"#, "network"),
+            (r#"{{ file_sha256 "Cargo.toml" }}"#, "fs_read"),
+            (r#"{{ base64_file "Cargo.toml" }}"#, "fs_read"),
+        ] {
+            let err = hbs.render_template(tmpl, &()).unwrap_err();
+            assert!(err.to_string().contains(capability), "{}: {}", tmpl, err);
+        }
+        assert_eq!(hbs.render_template(r#"{{ sha256 "" }}"#, &())?.len(), 64);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(feature = "string", feature = "json"))]
+    fn test_helpers_config() -> Result<(), Box<dyn Error>> {
+        let mut hbs = Handlebars::new();
+        hbs.register_helper(
+            "replace",
+            Box::new(
+                |_: &handlebars::Helper<'_>,
+                 _: &Handlebars<'_>,
+                 _: &handlebars::Context,
+                 _: &mut handlebars::RenderContext<'_, '_>,
+                 out: &mut dyn handlebars::Output|
+                 -> handlebars::HelperResult {
+                    out.write("mine")?;
+                    Ok(())
+                },
+            ),
+        );
+        HelpersConfig::new()
+            .include(["string", "file_name", "json_to_str"])
+            .exclude(["to_lower_case"])
+            .prefix("misc_")
+            .register(&mut hbs);
+        // strict mode and escape function are kept
+        assert!(!hbs.strict_mode());
+        assert_eq!(hbs.render_template("{{ foo }}", &())?, "");
+        assert_eq!(
+            hbs.render_template(r#"{{ misc_trim " <b> " }}"#, &())?,
+            "&lt;b&gt;"
+        );
+        for (tmpl, expected) in [
+            (r#"{{ replace "foo" "o" "a" }}"#, "mine"),
+            (r#"{{ misc_replace "foo" "o" "a" }}"#, "faa"),
+            (r#"{{ misc_to_upper_case "foo" }}"#, "FOO"),
+            (r#"{{ misc_file_name "bar/foo.txt" }}"#, "foo.txt"),
+            (
+                r#"{{ misc_to_upper_case (misc_file_name "bar/foo.txt") }}"#,
+                "FOO.TXT",
+            ),
+            (r#"{{ misc_json_to_str 42 }}"#, "42"),
+        ] {
+            assert_eq!(hbs.render_template(tmpl, &())?, expected, "{}", tmpl);
+        }
+        hbs.set_strict_mode(true);
+        for tmpl in [
+            r#"{{ misc_to_lower_case "FOO" }}"#,
+            r#"{{ misc_extension "foo.txt" }}"#,
+            r#"{{ misc_str_to_json "{}" }}"#,
+            r#"{{ to_upper_case "foo" }}"#,
+        ] {
+            assert!(hbs.render_template(tmpl, &()).is_err(), "{}", tmpl);
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(feature = "string", feature = "regex", feature = "datetime"))]
+    fn test_helpers_config_customized() -> Result<(), Box<dyn Error>> {
+        use chrono::{TimeZone, Utc};
+        let cache = Arc::new(regex_helpers::RegexCache::default());
+        cache.register_pattern("version", r"\d+\.\d+\.\d+")?;
+        let inflections = string_helpers::Inflections::new().irregular("schema", "schemata");
+        let mut hbs = Handlebars::new();
+        HelpersConfig::new()
+            .include(["to_plural", "regex_is_match", "now"])
+            .prefix("x_")
+            .inflections(Arc::new(inflections))
+            .regex_cache(cache)
+            .clock(Arc::new(datetime_helpers::FixedClock(
+                Utc.with_ymd_and_hms(2024, 3, 1, 10, 20, 30).unwrap(),
+            )))
+            .register(&mut hbs);
+        for (tmpl, expected) in [
+            (r#"{{ x_to_plural "schema" }}"#, "schemata"),
+            (
+                r#"{{ x_regex_is_match pattern_name="version" on="1.2.3" }}"#,
+                "true",
+            ),
+            (r#"{{ x_now }}"#, "2024-03-01T10:20:30Z"),
+        ] {
+            assert_eq!(hbs.render_template(tmpl, &())?, expected, "{}", tmpl);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_helpers_config_capabilities() -> Result<(), Box<dyn Error>> {
+        let mut hbs = Handlebars::new();
+        HelpersConfig::new()
+            .include(["env"])
+            .prefix("x_")
+            .capabilities(Capabilities::none())
+            .register(&mut hbs);
+        let err = hbs
+            .render_template(r#"{{ x_env_var "HOME" }}"#, &())
+            .unwrap_err();
+        assert!(err.to_string().contains("denied"), "{}", err);
+        hbs.set_strict_mode(true);
+        assert!(hbs
+            .render_template(r#"{{ x_read_to_str "." }}"#, &())
+            .is_err());
+        Ok(())
     }
 }
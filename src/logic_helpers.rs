@@ -0,0 +1,272 @@
+use handlebars::{handlebars_helper, JsonTruthy};
+use serde_json::Value as Json;
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::HelperRegistry;
+
+/// Convert a value into a string without the json quotes for string (and empty for null)
+pub(crate) fn to_plain_string(v: &Json) -> String {
+    match v {
+        Json::String(s) => s.to_owned(),
+        Json::Null => "".to_owned(),
+        _ => v.to_string(),
+    }
+}
+
+/// Return true if `haystack` contains `needle`:
+/// an item of an array, a key of an object, a substring of a string.
+pub(crate) fn contains(haystack: &Json, needle: &Json) -> bool {
+    match haystack {
+        Json::Array(items) => items.contains(needle),
+        Json::Object(m) => needle.as_str().map(|k| m.contains_key(k)).unwrap_or(false),
+        Json::String(s) => s.contains(&to_plain_string(needle)),
+        _ => false,
+    }
+}
+
+/// Empty values are `null`, `""`, `[]` and `{}` (`0` and `false` are not empty)
+pub(crate) fn is_empty(v: &Json) -> bool {
+    match v {
+        Json::Null => true,
+        Json::String(s) => s.is_empty(),
+        Json::Array(a) => a.is_empty(),
+        Json::Object(o) => o.is_empty(),
+        Json::Bool(_) | Json::Number(_) => false,
+    }
+}
+
+fn type_of(v: &Json) -> &'static str {
+    match v {
+        Json::Null => "null",
+        Json::Bool(_) => "boolean",
+        Json::Number(_) => "number",
+        Json::String(_) => "string",
+        Json::Array(_) => "array",
+        Json::Object(_) => "object",
+    }
+}
+
+/// Return the first truthy value (same rules as `#if`), or the last value if none is truthy
+fn default(args: &[&Json]) -> Json {
+    args.iter()
+        .find(|v| v.is_truthy(false))
+        .or(args.last())
+        .map(|v| (*v).clone())
+        .unwrap_or(Json::Null)
+}
+
+/// Return the first non-empty value (see [`is_empty`]) preserving its type, or `null`
+fn first_non_empty_value(args: &[&Json]) -> Json {
+    args.iter()
+        .find(|v| !is_empty(v))
+        .map(|v| (*v).clone())
+        .unwrap_or(Json::Null)
+}
+
+fn coalesce(args: &[&Json]) -> Json {
+    args.iter()
+        .find(|v| !v.is_null())
+        .map(|v| (*v).clone())
+        .unwrap_or(Json::Null)
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "logic", feature: Some("logic");
+    "default"(values: "any*") -> "any" {
+        r##"{{ default "" null 33 }}"## => "33"
+    }
+    "coalesce"(values: "any*") -> "any" {
+        r##"{{ coalesce null false 33 }}"## => "false"
+    }
+    "first_non_empty_value"(values: "any*") -> "any" {
+        r##"{{ first_non_empty_value "" 0 "foo" }}"## => "0"
+    }
+    "ternary"(cond: "any", then: "any", else: "any") -> "any" {
+        r##"{{ ternary (eq 1 2) "yes" "no" }}"## => "no"
+    }
+    "if_else"(cond: "any", then: "any", else: "any") -> "any" {
+        r##"{{ if_else true "yes" "no" }}"## => "yes"
+    }
+    "is_empty"(v: "any") -> "boolean" {
+        r##"{{ is_empty [] }}"## => "true"
+    }
+    "is_null"(v: "any") -> "boolean" {
+        r##"{{ is_null "" }}"## => "false"
+    }
+    "type_of"(v: "any") -> "string" {
+        r##"{{ type_of [1] }}"## => "array"
+    }
+    "starts_with"(s: "string", prefix: "string") -> "boolean" {
+        r##"{{ starts_with "foobar" "foo" }}"## => "true"
+    }
+    "ends_with"(s: "string", suffix: "string") -> "boolean" {
+        r##"{{ ends_with "foobar" "bar" }}"## => "true"
+    }
+    "contains"(haystack: "array|object|string", needle: "any") -> "boolean" {
+        r##"{{ contains [1, 2, 3] 2 }}"## => "true"
+    }
+    "in"(needle: "any", haystack: "array|object|string") -> "boolean" {
+        r##"{{ in "a" {"a": 1} }}"## => "true"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    {
+        handlebars_helper!(default_fct: |*args| default(&args));
+        handlebars.register_helper("default", Box::new(default_fct))
+    }
+    {
+        handlebars_helper!(coalesce_fct: |*args| coalesce(&args));
+        handlebars.register_helper("coalesce", Box::new(coalesce_fct))
+    }
+    {
+        handlebars_helper!(first_non_empty_value_fct: |*args| first_non_empty_value(&args));
+        handlebars.register_helper("first_non_empty_value", Box::new(first_non_empty_value_fct))
+    }
+    {
+        handlebars_helper!(ternary: |cond: Json, then_v: Json, else_v: Json| {
+            if cond.is_truthy(false) { then_v.clone() } else { else_v.clone() }
+        });
+        handlebars.register_helper("ternary", Box::new(ternary));
+        handlebars.register_helper("if_else", Box::new(ternary))
+    }
+    {
+        handlebars_helper!(is_empty_fct: |v: Json| is_empty(v));
+        handlebars.register_helper("is_empty", Box::new(is_empty_fct))
+    }
+    {
+        handlebars_helper!(is_null: |v: Json| v.is_null());
+        handlebars.register_helper("is_null", Box::new(is_null))
+    }
+    {
+        handlebars_helper!(type_of_fct: |v: Json| type_of(v));
+        handlebars.register_helper("type_of", Box::new(type_of_fct))
+    }
+    {
+        handlebars_helper!(starts_with: |s: str, prefix: str| s.starts_with(prefix));
+        handlebars.register_helper("starts_with", Box::new(starts_with))
+    }
+    {
+        handlebars_helper!(ends_with: |s: str, suffix: str| s.ends_with(suffix));
+        handlebars.register_helper("ends_with", Box::new(ends_with))
+    }
+    {
+        handlebars_helper!(contains_fct: |haystack: Json, needle: Json| contains(haystack, needle));
+        handlebars.register_helper("contains", Box::new(contains_fct))
+    }
+    {
+        handlebars_helper!(in_fct: |needle: Json, haystack: Json| contains(haystack, needle));
+        handlebars.register_helper("in", Box::new(in_fct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_renders;
+    use std::error::Error;
+
+    #[test]
+    fn test_default_coalesce() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ default 0 false "foo" }}"##, r##"foo"##),
+            (r##"{{ default "" 0 }}"##, r##"0"##),
+            (r##"{{ default [] "fallback" }}"##, r##"fallback"##),
+            (r##"{{ default }}"##, r##""##),
+            (r##"{{ coalesce null "" 33 }}"##, r##""##),
+            (r##"{{ coalesce null null }}"##, r##""##),
+            (r##"{{ len (coalesce null [1, 2]) }}"##, r##"2"##),
+        ]
+    }
+
+    #[test]
+    fn test_first_non_empty_value() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ first_non_empty_value "" null 33 }}"##, r##"33"##),
+            (
+                r##"{{ first_non_empty_value null false "foo" }}"##,
+                r##"false"##
+            ),
+            (r##"{{ first_non_empty_value [] {} "foo" }}"##, r##"foo"##),
+            (r##"{{ first_non_empty_value "" null }}"##, r##""##),
+            (
+                r##"{{ type_of (first_non_empty_value "" null) }}"##,
+                r##"null"##
+            ),
+            (
+                r##"{{ type_of (first_non_empty_value "" 33) }}"##,
+                r##"number"##
+            ),
+            (r##"{{ len (first_non_empty_value [] [1, 2]) }}"##, r##"2"##),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_first_non_empty_value_keeps_structure() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ json_to_str (first_non_empty_value {} {"a": [1, true]}) }}"##,
+                r##"{"a":[1,true]}"##
+            ),
+            (
+                r##"{{ set x=(first_non_empty_value null [1]) }}{{ json_to_str x }}"##,
+                r##"[1]"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_ternary() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ ternary true "yes" "no" }}"##, r##"yes"##),
+            (r##"{{ ternary 0 "yes" "no" }}"##, r##"no"##),
+            (r##"{{ if_else "x" 1 2 }}"##, r##"1"##),
+            (r##"{{ if_else (eq 1 2) 1 2 }}"##, r##"2"##),
+        ]
+    }
+
+    #[test]
+    fn test_is_empty_is_null_type_of() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ is_empty null }}"##, r##"true"##),
+            (r##"{{ is_empty "" }}"##, r##"true"##),
+            (r##"{{ is_empty {} }}"##, r##"true"##),
+            (r##"{{ is_empty 0 }}"##, r##"false"##),
+            (r##"{{ is_empty false }}"##, r##"false"##),
+            (r##"{{ is_empty " " }}"##, r##"false"##),
+            (r##"{{ is_null null }}"##, r##"true"##),
+            (r##"{{ type_of null }}"##, r##"null"##),
+            (r##"{{ type_of true }}"##, r##"boolean"##),
+            (r##"{{ type_of 1.5 }}"##, r##"number"##),
+            (r##"{{ type_of "1" }}"##, r##"string"##),
+            (r##"{{ type_of {"a": 1} }}"##, r##"object"##),
+        ]
+    }
+
+    #[test]
+    fn test_starts_with_ends_with() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ starts_with "foobar" "bar" }}"##, r##"false"##),
+            (r##"{{ ends_with "foobar" "" }}"##, r##"true"##),
+            (
+                r##"{{#if (starts_with "v1.2" "v") }}tag{{/if}}"##,
+                r##"tag"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_contains_in() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ contains [1, 2, 3] "2" }}"##, r##"false"##),
+            (r##"{{ contains {"a": 1} "a" }}"##, r##"true"##),
+            (r##"{{ contains {"a": 1} "b" }}"##, r##"false"##),
+            (r##"{{ contains "foobar" "oba" }}"##, r##"true"##),
+            (r##"{{ contains null "a" }}"##, r##"false"##),
+            (r##"{{ in 2 [1, 2, 3] }}"##, r##"true"##),
+            (r##"{{ in "c" {"a": 1} }}"##, r##"false"##),
+            (r##"{{ in "oo" "foo" }}"##, r##"true"##),
+            (r##"{{#if (in "y" ["x", "y"]) }}ok{{/if}}"##, r##"ok"##),
+        ]
+    }
+}
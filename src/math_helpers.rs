@@ -0,0 +1,544 @@
+use handlebars::{handlebars_helper, RenderError, RenderErrorReason};
+use serde_json::Value as Json;
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::HelperRegistry;
+
+/// A number as integer when possible, as float else.
+///
+/// Operations on integers return an integer (if no overflow),
+/// operations involving a float return a float.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// Read a number from a json number or from a string (like `"42"`, `"3.14"`)
+    fn from_json(helper: &str, v: &Json) -> Result<Number, RenderError> {
+        let n = match v {
+            Json::Number(n) => n
+                .as_i64()
+                .map(Number::Int)
+                .or_else(|| n.as_f64().map(Number::Float)),
+            Json::String(s) => {
+                let s = s.trim();
+                s.parse::<i64>()
+                    .map(Number::Int)
+                    .ok()
+                    .or_else(|| s.parse::<f64>().ok().map(Number::Float))
+            }
+            _ => None,
+        };
+        n.ok_or_else(|| crate::to_other_error(format!("{}: '{}' is not a number", helper, v)))
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(v) => v as f64,
+            Number::Float(v) => v,
+        }
+    }
+
+    fn into_json(self, helper: &str) -> Result<Json, RenderError> {
+        match self {
+            Number::Int(v) => Ok(Json::from(v)),
+            Number::Float(v) => serde_json::Number::from_f64(v)
+                .map(Json::Number)
+                .ok_or_else(|| {
+                    crate::to_other_error(format!(
+                        "{}: result '{}' is not a valid number",
+                        helper, v
+                    ))
+                }),
+        }
+    }
+
+    fn apply(
+        self,
+        other: Number,
+        int_op: impl Fn(i64, i64) -> Option<i64>,
+        float_op: impl Fn(f64, f64) -> f64,
+    ) -> Number {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => int_op(a, b)
+                .map(Number::Int)
+                .unwrap_or_else(|| Number::Float(float_op(a as f64, b as f64))),
+            _ => Number::Float(float_op(self.as_f64(), other.as_f64())),
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self.as_f64() == 0.0
+    }
+}
+
+/// Collect the numbers from the params, a single array param is used as the list of numbers
+fn numbers(helper: &str, args: &[&Json]) -> Result<Vec<Number>, RenderError> {
+    let values: Vec<&Json> = match args {
+        [Json::Array(items)] => items.iter().collect(),
+        _ => args.to_vec(),
+    };
+    if values.is_empty() {
+        return Err(crate::to_other_error(format!(
+            "{}: requires at least one number",
+            helper
+        )));
+    }
+    values
+        .into_iter()
+        .map(|v| Number::from_json(helper, v))
+        .collect()
+}
+
+fn fold(
+    helper: &str,
+    args: &[&Json],
+    int_op: impl Fn(i64, i64) -> Option<i64>,
+    float_op: impl Fn(f64, f64) -> f64,
+) -> Result<Json, RenderError> {
+    let mut values = numbers(helper, args)?.into_iter();
+    let first = values.next().unwrap_or(Number::Int(0));
+    values
+        .fold(first, |acc, v| acc.apply(v, &int_op, &float_op))
+        .into_json(helper)
+}
+
+fn pick(
+    helper: &str,
+    args: &[&Json],
+    keep_first: fn(f64, f64) -> bool,
+) -> Result<Json, RenderError> {
+    let mut values = numbers(helper, args)?.into_iter();
+    let first = values.next().unwrap_or(Number::Int(0));
+    values
+        .fold(first, |acc, v| {
+            if keep_first(acc.as_f64(), v.as_f64()) {
+                acc
+            } else {
+                v
+            }
+        })
+        .into_json(helper)
+}
+
+fn div_numbers(a: &Json, b: &Json) -> Result<Json, RenderError> {
+    let a = Number::from_json("div", a)?;
+    let b = Number::from_json("div", b)?;
+    if b.is_zero() {
+        return Err(crate::to_other_error("div: division by zero"));
+    }
+    a.apply(
+        b,
+        |a, b| {
+            a.checked_rem(b)
+                .filter(|r| *r == 0)
+                .and_then(|_| a.checked_div(b))
+        },
+        |a, b| a / b,
+    )
+    .into_json("div")
+}
+
+fn mod_numbers(a: &Json, b: &Json) -> Result<Json, RenderError> {
+    let a = Number::from_json("mod", a)?;
+    let b = Number::from_json("mod", b)?;
+    if b.is_zero() {
+        return Err(crate::to_other_error("mod: division by zero"));
+    }
+    a.apply(b, |a, b| a.checked_rem(b), |a, b| a % b)
+        .into_json("mod")
+}
+
+fn pow_numbers(a: &Json, b: &Json) -> Result<Json, RenderError> {
+    let a = Number::from_json("pow", a)?;
+    let b = Number::from_json("pow", b)?;
+    a.apply(
+        b,
+        |a, b| u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+        f64::powf,
+    )
+    .into_json("pow")
+}
+
+/// Round with `op` to `precision` decimals, with a precision of 0 the result is an integer
+fn round_with(
+    helper: &str,
+    v: &Json,
+    precision: u64,
+    op: fn(f64) -> f64,
+) -> Result<Json, RenderError> {
+    let result = match Number::from_json(helper, v)? {
+        Number::Int(v) => Number::Int(v),
+        Number::Float(v) if precision == 0 => {
+            let r = op(v);
+            if r.abs() < i64::MAX as f64 {
+                Number::Int(r as i64)
+            } else {
+                Number::Float(r)
+            }
+        }
+        Number::Float(v) => {
+            let factor = 10f64.powi(precision.min(i32::MAX as u64) as i32);
+            Number::Float(op(v * factor) / factor)
+        }
+    };
+    result.into_json(helper)
+}
+
+fn abs_number(v: &Json) -> Result<Json, RenderError> {
+    match Number::from_json("abs", v)? {
+        Number::Int(v) => v
+            .checked_abs()
+            .map(Number::Int)
+            .unwrap_or(Number::Float((v as f64).abs())),
+        Number::Float(v) => Number::Float(v.abs()),
+    }
+    .into_json("abs")
+}
+
+fn insert_thousands_sep(int_part: &str, sep: &str) -> String {
+    let digits: Vec<char> = int_part.chars().collect();
+    let mut result = String::with_capacity(int_part.len() + int_part.len() / 3 * sep.len());
+    for (i, c) in digits.iter().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push_str(sep);
+        }
+        result.push(*c);
+    }
+    result
+}
+
+/// Maximum number of decimals of `number_format`
+const DECIMALS_MAX: u64 = 20;
+
+/// The optional `decimals` of `number_format`, an integer between 0 and [`DECIMALS_MAX`]
+fn to_decimals(v: Option<&Json>) -> Result<Option<u64>, RenderError> {
+    let Some(v) = v else {
+        return Ok(None);
+    };
+    let decimals = v.as_u64().ok_or_else(|| {
+        RenderErrorReason::HashTypeMismatchForName(
+            "number_format",
+            "decimals".to_string(),
+            "non-negative integer".to_string(),
+        )
+    })?;
+    if decimals > DECIMALS_MAX {
+        return Err(crate::to_other_error(format!(
+            "number_format: decimals {} is greater than the maximum {}",
+            decimals, DECIMALS_MAX
+        )));
+    }
+    Ok(Some(decimals))
+}
+
+fn format_decimal(
+    v: Number,
+    decimals: Option<u64>,
+    thousands_sep: &str,
+    decimal_sep: &str,
+) -> String {
+    let s = match (v, decimals) {
+        (Number::Int(v), None) => v.to_string(),
+        (Number::Float(v), None) => v.to_string(),
+        (_, Some(d)) => format!("{:.*}", d as usize, v.as_f64()),
+    };
+    let (sign, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", s.as_str()),
+    };
+    let (int_part, frac_part) = match unsigned.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (unsigned, None),
+    };
+    let mut result = format!("{}{}", sign, insert_thousands_sep(int_part, thousands_sep));
+    if let Some(f) = frac_part {
+        result.push_str(decimal_sep);
+        result.push_str(f);
+    }
+    result
+}
+
+fn format_bytes(
+    v: Number,
+    decimals: Option<u64>,
+    thousands_sep: &str,
+    decimal_sep: &str,
+    base: f64,
+    units: &[&str],
+) -> String {
+    let mut value = v.as_f64();
+    let mut unit = 0;
+    while value.abs() >= base && unit < units.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+    let formatted = if unit == 0 {
+        format_decimal(
+            Number::Float(value.trunc()),
+            Some(0),
+            thousands_sep,
+            decimal_sep,
+        )
+    } else {
+        format_decimal(
+            Number::Float(value),
+            Some(decimals.unwrap_or(1)),
+            thousands_sep,
+            decimal_sep,
+        )
+    };
+    format!("{} {}", formatted, units[unit])
+}
+
+fn format_number(
+    v: &Json,
+    decimals: Option<u64>,
+    thousands_sep: &str,
+    decimal_sep: &str,
+    style: &str,
+) -> Result<String, RenderError> {
+    let v = Number::from_json("number_format", v)?;
+    match style {
+        "decimal" => Ok(format_decimal(v, decimals, thousands_sep, decimal_sep)),
+        "bytes" => Ok(format_bytes(
+            v,
+            decimals,
+            thousands_sep,
+            decimal_sep,
+            1024.0,
+            &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"],
+        )),
+        "si_bytes" => Ok(format_bytes(
+            v,
+            decimals,
+            thousands_sep,
+            decimal_sep,
+            1000.0,
+            &["B", "kB", "MB", "GB", "TB", "PB", "EB"],
+        )),
+        _ => Err(crate::to_other_error(format!(
+            "number_format: style '{}' unknown (expected: decimal, bytes, si_bytes)",
+            style
+        ))),
+    }
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "math", feature: Some("math");
+    "add"(n: "number+|array") -> "number" {
+        r##"{{ add 1 2 3 }}"## => "6"
+    }
+    "sub"(a: "number", b: "number") -> "number" {
+        r##"{{ sub 1 1.5 }}"## => "-0.5"
+    }
+    "mul"(n: "number+|array") -> "number" {
+        r##"{{ mul 2 3 4 }}"## => "24"
+    }
+    "div"(a: "number", b: "number") -> "number" {
+        r##"{{ div 7 2 }}"## => "3.5"
+    }
+    "mod"(a: "number", b: "number") -> "number" {
+        r##"{{ mod 7 3 }}"## => "1"
+    }
+    "min"(n: "number+|array") -> "number" {
+        r##"{{ min 3 1 2 }}"## => "1"
+    }
+    "max"(n: "number+|array") -> "number" {
+        r##"{{ max [3, 1, 2] }}"## => "3"
+    }
+    "pow"(a: "number", b: "number") -> "number" {
+        r##"{{ pow 2 10 }}"## => "1024"
+    }
+    "abs"(n: "number") -> "number" {
+        r##"{{ abs -3 }}"## => "3"
+    }
+    "round"(n: "number") [precision: "integer" = "0"] -> "number" {
+        r##"{{ round 3.14159 precision=2 }}"## => "3.14"
+    }
+    "floor"(n: "number") [precision: "integer" = "0"] -> "number" {
+        r##"{{ floor 2.7 }}"## => "2"
+    }
+    "ceil"(n: "number") [precision: "integer" = "0"] -> "number" {
+        r##"{{ ceil 2.1 }}"## => "3"
+    }
+    "number_format"(n: "number") [decimals: "integer", thousands_sep: "string" = ",", decimal_sep: "string" = ".", style: "string" = "decimal"] -> "string" {
+        r##"{{ number_format 1234567.891 decimals=2 }}"## => "1,234,567.89"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    {
+        handlebars_helper!(add: |*args| fold("add", &args, i64::checked_add, |a, b| a + b)?);
+        handlebars.register_helper("add", Box::new(add))
+    }
+    {
+        handlebars_helper!(sub: |a: Json, b: Json| fold("sub", &[a, b], i64::checked_sub, |a, b| a - b)?);
+        handlebars.register_helper("sub", Box::new(sub))
+    }
+    {
+        handlebars_helper!(mul: |*args| fold("mul", &args, i64::checked_mul, |a, b| a * b)?);
+        handlebars.register_helper("mul", Box::new(mul))
+    }
+    {
+        handlebars_helper!(div: |a: Json, b: Json| div_numbers(a, b)?);
+        handlebars.register_helper("div", Box::new(div))
+    }
+    {
+        handlebars_helper!(mod_fct: |a: Json, b: Json| mod_numbers(a, b)?);
+        handlebars.register_helper("mod", Box::new(mod_fct))
+    }
+    {
+        handlebars_helper!(min: |*args| pick("min", &args, |a, b| a <= b)?);
+        handlebars.register_helper("min", Box::new(min))
+    }
+    {
+        handlebars_helper!(max: |*args| pick("max", &args, |a, b| a >= b)?);
+        handlebars.register_helper("max", Box::new(max))
+    }
+    {
+        handlebars_helper!(pow: |a: Json, b: Json| pow_numbers(a, b)?);
+        handlebars.register_helper("pow", Box::new(pow))
+    }
+    {
+        handlebars_helper!(abs: |v: Json| abs_number(v)?);
+        handlebars.register_helper("abs", Box::new(abs))
+    }
+    {
+        handlebars_helper!(round: |v: Json, {precision: u64 = 0}| round_with("round", v, precision, f64::round)?);
+        handlebars.register_helper("round", Box::new(round))
+    }
+    {
+        handlebars_helper!(floor: |v: Json, {precision: u64 = 0}| round_with("floor", v, precision, f64::floor)?);
+        handlebars.register_helper("floor", Box::new(floor))
+    }
+    {
+        handlebars_helper!(ceil: |v: Json, {precision: u64 = 0}| round_with("ceil", v, precision, f64::ceil)?);
+        handlebars.register_helper("ceil", Box::new(ceil))
+    }
+    {
+        handlebars_helper!(number_format: |v: Json, {thousands_sep: str = ",", decimal_sep: str = ".", style: str = "decimal"}, **kwargs| {
+            let decimals = to_decimals(kwargs.get("decimals").copied())?;
+            format_number(v, decimals, thousands_sep, decimal_sep, style)?
+        });
+        handlebars.register_helper("number_format", Box::new(number_format))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_renders;
+    use std::error::Error;
+
+    #[test]
+    fn test_add_sub_mul() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ add 1 2 }}"##, r##"3"##),
+            (r##"{{ add 1 0.5 }}"##, r##"1.5"##),
+            (r##"{{ add "41" 1 }}"##, r##"42"##),
+            (r##"{{ add [1, 2, 3] }}"##, r##"6"##),
+            (
+                r##"{{ add 9223372036854775807 1 }}"##,
+                r##"9.223372036854776e+18"##
+            ),
+            (r##"{{ sub 10 3 }}"##, r##"7"##),
+            (r##"{{ mul 2 1.5 }}"##, r##"3.0"##),
+            (r##"{{ add (mul 2 3) 1 }}"##, r##"7"##),
+        ]
+    }
+
+    #[test]
+    fn test_div_mod() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ div 6 3 }}"##, r##"2"##),
+            (r##"{{ div 7.5 2.5 }}"##, r##"3.0"##),
+            (r##"{{ mod -7 3 }}"##, r##"-1"##),
+            (r##"{{ mod 7.5 2 }}"##, r##"1.5"##),
+        ]
+    }
+
+    #[test]
+    fn test_min_max() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ max 3 1 2 }}"##, r##"3"##),
+            (r##"{{ max 3 1.5 "4" }}"##, r##"4"##),
+            (r##"{{ min [3, 1.5, 2] }}"##, r##"1.5"##),
+            (r##"{{ max 7 }}"##, r##"7"##),
+        ]
+    }
+
+    #[test]
+    fn test_round_floor_ceil_abs_pow() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ round 2.5 }}"##, r##"3"##),
+            (r##"{{ round 2.4 }}"##, r##"2"##),
+            (r##"{{ round 2 }}"##, r##"2"##),
+            (r##"{{ floor -2.2 }}"##, r##"-3"##),
+            (r##"{{ ceil 2.121 precision=1 }}"##, r##"2.2"##),
+            (r##"{{ abs -3.5 }}"##, r##"3.5"##),
+            (r##"{{ pow 2 -1 }}"##, r##"0.5"##),
+            (r##"{{ pow 4 0.5 }}"##, r##"2.0"##),
+        ]
+    }
+
+    #[test]
+    fn test_number_format() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ number_format 1234567 }}"##, r##"1,234,567"##),
+            (
+                r##"{{ number_format -1234567.891 }}"##,
+                r##"-1,234,567.891"##
+            ),
+            (
+                r##"{{ number_format 1234.5 decimals=2 }}"##,
+                r##"1,234.50"##
+            ),
+            (r##"{{ number_format 1234.567 decimals=0 }}"##, r##"1,235"##),
+            (
+                r##"{{ number_format 1234567.891 decimals=2 thousands_sep=" " decimal_sep="," }}"##,
+                r##"1 234 567,89"##
+            ),
+            (r##"{{ number_format 123 }}"##, r##"123"##),
+            (
+                r##"{{ number_format 0.5 decimals=20 }}"##,
+                r##"0.50000000000000000000"##
+            ),
+            (r##"{{ number_format 512 style="bytes" }}"##, r##"512 B"##),
+            (
+                r##"{{ number_format 1258291 style="bytes" }}"##,
+                r##"1.2 MiB"##
+            ),
+            (
+                r##"{{ number_format 1536 style="bytes" decimals=2 }}"##,
+                r##"1.50 KiB"##
+            ),
+            (
+                r##"{{ number_format 1500000 style="si_bytes" }}"##,
+                r##"1.5 MB"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let hbs = crate::new_hbs();
+        let data = serde_json::json!({});
+        for tmpl in [
+            r##"{{ add 1 "foo" }}"##,
+            r##"{{ add }}"##,
+            r##"{{ add 1 null }}"##,
+            r##"{{ div 1 0 }}"##,
+            r##"{{ mod 1 0 }}"##,
+            r##"{{ number_format 1 style="foo" }}"##,
+            r##"{{ number_format 1.5 decimals=100000000000 }}"##,
+            r##"{{ number_format 1.5 decimals=21 }}"##,
+            r##"{{ number_format 1.5 decimals=-1 }}"##,
+            r##"{{ number_format 1.5 decimals=1.5 }}"##,
+            r##"{{ number_format 1.5 decimals="2" }}"##,
+        ] {
+            assert!(hbs.render_template(tmpl, &data).is_err(), "{}", tmpl);
+        }
+    }
+}
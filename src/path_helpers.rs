@@ -1,46 +1,163 @@
-use handlebars::{handlebars_helper, Handlebars};
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, JsonValue as Json, RenderContext, RenderError,
+    RenderErrorReason, ScopedJson,
+};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-fn expand(s: &str) -> PathBuf {
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::file_helpers::{check_path, FsSandbox};
+use crate::HelperRegistry;
+use crate::{Capabilities, Capability};
+
+fn expand(s: &str, sandbox: Option<&FsSandbox>, fs_read: bool) -> PathBuf {
     let p = PathBuf::from(s);
     // canonicalize to be able to extract file_name, parent, extension from path like '.'
     // without requested template author to call canonicalize in every place
-    if p.exists() {
+    // (only if allowed to read the file system and inside the sandbox, else the path is used as is)
+    let allowed = fs_read
+        && sandbox
+            .map(|sandbox| sandbox.check(s).is_ok())
+            .unwrap_or(true);
+    if allowed && p.exists() {
         p.canonicalize().unwrap_or(p)
     } else {
         p
     }
 }
 
-pub fn register(handlebars: &mut Handlebars) {
-    {
-        handlebars_helper!(parent: |v: str| {
-            expand(v).parent().and_then(|s| s.to_str()).unwrap_or("").to_owned()
-        });
-        handlebars.register_helper("parent", Box::new(parent))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathPart {
+    Parent,
+    FileName,
+    Extension,
+    Canonicalize,
+}
+
+impl PathPart {
+    const ALL: [PathPart; 4] = [
+        PathPart::Parent,
+        PathPart::FileName,
+        PathPart::Extension,
+        PathPart::Canonicalize,
+    ];
+
+    fn helper_name(&self) -> &'static str {
+        match self {
+            PathPart::Parent => "parent",
+            PathPart::FileName => "file_name",
+            PathPart::Extension => "extension",
+            PathPart::Canonicalize => "canonicalize",
+        }
     }
-    {
-        handlebars_helper!(file_name: |v: str| {
-            expand(v).file_name().and_then(|s| s.to_str()).unwrap_or("").to_owned()
-        });
-        handlebars.register_helper("file_name", Box::new(file_name))
+}
+
+#[allow(non_camel_case_types)]
+struct path_fct {
+    part: PathPart,
+    sandbox: Option<Arc<FsSandbox>>,
+    fs_read: bool,
+}
+
+impl HelperDef for path_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let helper_name = self.part.helper_name();
+        let v = h
+            .param(0)
+            .and_then(|v| v.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex(helper_name, 0))?;
+        let sandbox = self.sandbox.as_deref();
+        let res = match self.part {
+            PathPart::Parent => expand(v, sandbox, self.fs_read)
+                .parent()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_owned(),
+            PathPart::FileName => expand(v, sandbox, self.fs_read)
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_owned(),
+            PathPart::Extension => expand(v, sandbox, self.fs_read)
+                .extension()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_owned(),
+            PathPart::Canonicalize => {
+                check_path(helper_name, v, sandbox)?;
+                Path::new(v)
+                    .canonicalize()
+                    .ok()
+                    .and_then(|s| s.to_str().map(|v| v.to_owned()))
+                    .unwrap_or_else(|| "".into())
+            }
+        };
+        Ok(ScopedJson::Derived(Json::String(res)))
+    }
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "path", feature: None;
+    "parent"(path: "string") -> "string" {
+        r##"{{ parent "/hello/bar/foo.txt" }}"## => "/hello/bar"
+    }
+    "file_name"(path: "string") -> "string" {
+        r##"{{ file_name "/hello/bar/foo.txt" }}"## => "foo.txt"
+    }
+    "extension"(path: "string") -> "string" {
+        r##"{{ extension "/hello/bar/foo.txt" }}"## => "txt"
     }
-    {
-        handlebars_helper!(extension: |v: str| expand(v).extension().and_then(|s| s.to_str()).unwrap_or("").to_owned());
-        handlebars.register_helper("extension", Box::new(extension))
+    "canonicalize"(path: "string") -> "string", requires FsRead {
+        r##"{{ file_name (canonicalize "src/../Cargo.toml") }}"## => "Cargo.toml", in_repo
     }
-    {
-        handlebars_helper!(canonicalize: |v: str| {
-            Path::new(v).canonicalize().ok().and_then(|s| s.to_str().map(|v| v.to_owned())).unwrap_or_else(|| "".into())
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with_sandbox(handlebars, None)
+}
+
+/// Register the path helpers, `canonicalize` is restricted to the paths allowed by `sandbox` (if any),
+/// `parent`, `file_name`, `extension` don't resolve the paths outside of it
+pub fn register_with_sandbox(
+    handlebars: &mut impl HelperRegistry,
+    sandbox: Option<Arc<FsSandbox>>,
+) {
+    register_with(handlebars, sandbox, Capabilities::default())
+}
+
+/// Register the path helpers, without `fs_read` capability `parent`, `file_name`, `extension`
+/// don't resolve the paths (`canonicalize` requires `fs_read`, see [`HelperRegistry::register_helper_requiring`])
+pub fn register_with(
+    handlebars: &mut impl HelperRegistry,
+    sandbox: Option<Arc<FsSandbox>>,
+    capabilities: Capabilities,
+) {
+    for part in PathPart::ALL {
+        let def = Box::new(path_fct {
+            part,
+            sandbox: sandbox.clone(),
+            fs_read: capabilities.grants(Capability::FsRead),
         });
-        handlebars.register_helper("canonicalize", Box::new(canonicalize))
+        if part == PathPart::Canonicalize {
+            handlebars.register_helper_requiring(Capability::FsRead, part.helper_name(), def);
+        } else {
+            handlebars.register_helper(part.helper_name(), def);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::file_helpers::FsSandbox;
     use crate::tests::assert_helpers;
     use std::error::Error;
+    use std::sync::Arc;
 
     #[test]
     fn test_register_path_helpers() -> Result<(), Box<dyn Error>> {
@@ -89,4 +206,34 @@ mod tests {
         )?;
         Ok(())
     }
+
+    #[test]
+    fn test_path_helpers_sandbox() -> Result<(), Box<dyn Error>> {
+        let root = tempfile::tempdir()?;
+        std::fs::create_dir(root.path().join("sub"))?;
+        let mut hbs = crate::new_hbs();
+        super::register_with_sandbox(&mut hbs, Some(Arc::new(FsSandbox::new(root.path())?)));
+        let data = ();
+        let root_path = root.path().canonicalize()?;
+        let tmpl = format!(
+            "{{{{ canonicalize {:?} }}}}",
+            root.path().join("sub/../sub")
+        );
+        assert_eq!(
+            hbs.render_template(&tmpl, &data)?,
+            root_path.join("sub").to_string_lossy()
+        );
+        let tmpl = format!("{{{{ canonicalize {:?} }}}}", root.path().join(".."));
+        let err = hbs.render_template(&tmpl, &data).unwrap_err();
+        assert!(err.to_string().contains("canonicalize"), "{}", err);
+        assert_eq!(
+            hbs.render_template(r##"{{ file_name "/hello/bar/foo" }}"##, &data)?,
+            "foo"
+        );
+        assert_eq!(
+            hbs.render_template(r##"{{ parent "/hello/bar/../foo" }}"##, &data)?,
+            "/hello/bar/.."
+        );
+        Ok(())
+    }
 }
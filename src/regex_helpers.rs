@@ -2,8 +2,301 @@ use handlebars::{
     Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, RenderErrorReason,
     ScopedJson,
 };
-use regex::Regex;
+use regex::{Captures, Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use thiserror::Error;
 
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::HelperRegistry;
+
+const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+#[derive(Debug, Error)]
+pub enum RegexError {
+    #[error("unknown regex flag '{flag}' in '{flags}' (expected: i, m, s, x, U, u, R, and '-' to disable the following flags)")]
+    UnknownFlag { flag: char, flags: String },
+    #[error("regex pattern '{pattern}' exceeds the size limit of {limit} bytes (see size_limit)")]
+    SizeLimitExceeded { pattern: String, limit: usize },
+    #[error(transparent)]
+    InvalidPattern(regex::Error),
+}
+
+/// Options used to compile a pattern, mapped onto [`regex::RegexBuilder`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RegexOptions {
+    /// flags with the same meaning as the inline flags of the pattern syntax:
+    /// `i` case insensitive, `m` multi line, `s` dot matches new line, `x` ignore whitespace,
+    /// `U` swap greed, `u` unicode, `R` CRLF mode; flags following a `-` are disabled (eg `"i-u"`)
+    pub flags: String,
+    /// limit (in bytes) of the compiled regex, see [`RegexError::SizeLimitExceeded`]
+    pub size_limit: Option<usize>,
+    /// limit (in bytes) of the cache of the lazy DFA, exceeding it is not an error
+    pub dfa_size_limit: Option<usize>,
+}
+
+impl RegexOptions {
+    fn build(&self, pattern: &str) -> Result<Regex, RegexError> {
+        let mut builder = RegexBuilder::new(pattern);
+        let mut enabled = true;
+        for flag in self.flags.chars() {
+            match flag {
+                '-' => {
+                    enabled = false;
+                    continue;
+                }
+                'i' => builder.case_insensitive(enabled),
+                'm' => builder.multi_line(enabled),
+                's' => builder.dot_matches_new_line(enabled),
+                'x' => builder.ignore_whitespace(enabled),
+                'U' => builder.swap_greed(enabled),
+                'u' => builder.unicode(enabled),
+                'R' => builder.crlf(enabled),
+                _ => {
+                    return Err(RegexError::UnknownFlag {
+                        flag,
+                        flags: self.flags.clone(),
+                    })
+                }
+            };
+        }
+        if let Some(limit) = self.size_limit {
+            builder.size_limit(limit);
+        }
+        if let Some(limit) = self.dfa_size_limit {
+            builder.dfa_size_limit(limit);
+        }
+        builder.build().map_err(|err| match err {
+            regex::Error::CompiledTooBig(limit) => RegexError::SizeLimitExceeded {
+                pattern: pattern.to_string(),
+                limit,
+            },
+            _ => RegexError::InvalidPattern(err),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RegexKey {
+    pattern: String,
+    options: RegexOptions,
+}
+
+/// Bounded cache of compiled regular expressions.
+///
+/// The cache is shared by the regex helpers of a registry, so a pattern used
+/// inside a loop is compiled once (per set of options). When the capacity is reached, the least
+/// recently used regex is dropped. A capacity of `0` disables the caching.
+///
+/// Patterns can also be registered under a name (from Rust) and be used in
+/// templates with `pattern_name="..."` instead of `pattern="..."`.
+pub struct RegexCache {
+    capacity: usize,
+    state: Mutex<RegexCacheState>,
+}
+
+#[derive(Default)]
+struct RegexCacheState {
+    tick: u64,
+    compiled: HashMap<RegexKey, (Regex, u64)>,
+    named: HashMap<String, String>,
+}
+
+impl Default for RegexCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl RegexCache {
+    pub fn with_capacity(capacity: usize) -> Self {
+        RegexCache {
+            capacity,
+            state: Mutex::new(RegexCacheState::default()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of compiled regex currently in the cache
+    pub fn len(&self) -> usize {
+        self.lock().compiled.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Register a pattern under `name`, the pattern is compiled (and so validated) immediately.
+    pub fn register_pattern(&self, name: &str, pattern: &str) -> Result<(), RegexError> {
+        self.get_or_compile(pattern, &RegexOptions::default())?;
+        self.lock()
+            .named
+            .insert(name.to_string(), pattern.to_string());
+        Ok(())
+    }
+
+    /// Return the pattern registered under `name`
+    pub fn named_pattern(&self, name: &str) -> Option<String> {
+        self.lock().named.get(name).cloned()
+    }
+
+    /// Return the compiled regex for `pattern` and `options` (compile & cache it if needed).
+    pub fn get_or_compile(
+        &self,
+        pattern: &str,
+        options: &RegexOptions,
+    ) -> Result<Regex, RegexError> {
+        let key = RegexKey {
+            pattern: pattern.to_string(),
+            options: options.clone(),
+        };
+        {
+            let mut state = self.lock();
+            state.tick += 1;
+            let tick = state.tick;
+            if let Some((re, last_used)) = state.compiled.get_mut(&key) {
+                *last_used = tick;
+                return Ok(re.clone());
+            }
+        }
+        // compile outside of the lock, compilation of a pattern could be slow
+        let re = options.build(pattern)?;
+        if self.capacity > 0 {
+            let mut state = self.lock();
+            if state.compiled.len() >= self.capacity && !state.compiled.contains_key(&key) {
+                let lru = state
+                    .compiled
+                    .iter()
+                    .min_by_key(|(_, (_, last_used))| *last_used)
+                    .map(|(k, _)| k.clone());
+                if let Some(k) = lru {
+                    state.compiled.remove(&k);
+                }
+            }
+            let tick = state.tick;
+            state.compiled.insert(key, (re.clone(), tick));
+        }
+        Ok(re)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RegexCacheState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn find_str_hash<'a>(
+    h: &'a Helper,
+    helper_name: &'static str,
+    name: &str,
+) -> Result<&'a str, RenderError> {
+    h.hash_get(name)
+        .and_then(|v| v.value().as_str())
+        .ok_or_else(|| {
+            RenderErrorReason::ParamNotFoundForName(helper_name, name.to_string()).into()
+        })
+}
+
+fn find_regex(
+    h: &Helper,
+    helper_name: &'static str,
+    cache: &RegexCache,
+) -> Result<Regex, RenderError> {
+    let pattern = match h.hash_get("pattern_name").and_then(|v| v.value().as_str()) {
+        Some(name) => cache.named_pattern(name).ok_or_else(|| {
+            crate::to_other_error(format!(
+                "{}: no pattern registered with the name '{}'",
+                helper_name, name
+            ))
+        })?,
+        None => find_str_hash(h, helper_name, "pattern")?.to_string(),
+    };
+    let flags = match h.hash_get("flags").map(|v| v.value()) {
+        None => "",
+        Some(v) => v.as_str().ok_or_else(|| {
+            RenderErrorReason::HashTypeMismatchForName(
+                helper_name,
+                "flags".to_string(),
+                "string".to_string(),
+            )
+        })?,
+    };
+    let options = RegexOptions {
+        flags: flags.to_string(),
+        size_limit: find_usize_hash(h, helper_name, "size_limit")?,
+        dfa_size_limit: find_usize_hash(h, helper_name, "dfa_size_limit")?,
+    };
+    cache
+        .get_or_compile(&pattern, &options)
+        .map_err(|err| crate::to_other_error(err.to_string()))
+}
+
+/// Return the optional hash value `name`, fail if it's not a non-negative integer
+fn find_usize_hash(
+    h: &Helper,
+    helper_name: &'static str,
+    name: &str,
+) -> Result<Option<usize>, RenderError> {
+    h.hash_get(name)
+        .map(|v| {
+            v.value()
+                .as_u64()
+                .and_then(|v| usize::try_from(v).ok())
+                .ok_or_else(|| {
+                    RenderErrorReason::HashTypeMismatchForName(
+                        helper_name,
+                        name.to_string(),
+                        "non-negative integer".to_string(),
+                    )
+                    .into()
+                })
+        })
+        .transpose()
+}
+
+fn captures_to_json(re: &Regex, caps: &Captures) -> serde_json::Value {
+    let collected = re
+        .capture_names()
+        .filter_map(|v| {
+            v.and_then(|name| {
+                caps.name(name).map(|m| {
+                    (
+                        name.to_string(),
+                        serde_json::Value::String(m.as_str().to_string()),
+                    )
+                })
+            })
+        })
+        .chain(caps.iter().enumerate().filter_map(|(i, mm)| {
+            mm.map(|m| {
+                (
+                    format!("_{}", i),
+                    serde_json::Value::String(m.as_str().to_string()),
+                )
+            })
+        }))
+        .collect::<serde_json::Map<_, _>>();
+    serde_json::Value::Object(collected)
+}
+
+/// Cache used by the helpers constructed without a cache ([`regex_captures_fct`], [`regex_is_match_fct`])
+fn default_cache() -> &'static RegexCache {
+    static CACHE: OnceLock<RegexCache> = OnceLock::new();
+    CACHE.get_or_init(RegexCache::default)
+}
+
+fn regex_captures(h: &Helper, cache: &RegexCache) -> Result<serde_json::Value, RenderError> {
+    let on = find_str_hash(h, "regex_captures", "on")?;
+    let re = find_regex(h, "regex_captures", cache)?;
+    Ok(re
+        .captures(on)
+        .map(|caps| captures_to_json(&re, &caps))
+        .unwrap_or(serde_json::Value::Null))
+}
+
+/// `regex_captures` using a cache shared by the process (use [`register_with_cache`] to provide one)
 #[allow(non_camel_case_types)]
 pub struct regex_captures_fct;
 
@@ -15,46 +308,57 @@ impl HelperDef for regex_captures_fct {
         _: &'rc Context,
         _: &mut RenderContext<'reg, 'rc>,
     ) -> Result<ScopedJson<'reg>, RenderError> {
-        let on = h.hash_get("on").and_then(|v| v.value().as_str()).ok_or(
-            RenderErrorReason::ParamNotFoundForName("regex_captures", "on".to_string()),
-        )?;
-        let pattern = h
-            .hash_get("pattern")
-            .and_then(|v| v.value().as_str())
-            .ok_or(RenderErrorReason::ParamNotFoundForName(
-                "regex_captures",
-                "pattern".to_string(),
-            ))?;
-        let re = Regex::new(pattern).map_err(|err| crate::to_other_error(err.to_string()))?;
-        if let Some(caps) = re.captures(on) {
-            let collected = re
-                .capture_names()
-                .filter_map(|v| {
-                    v.and_then(|name| {
-                        caps.name(name).map(|m| {
-                            (
-                                name.to_string(),
-                                serde_json::Value::String(m.as_str().to_string()),
-                            )
-                        })
-                    })
-                })
-                .chain(caps.iter().enumerate().filter_map(|(i, mm)| {
-                    mm.map(|m| {
-                        (
-                            format!("_{}", i),
-                            serde_json::Value::String(m.as_str().to_string()),
-                        )
-                    })
-                }))
-                .collect::<serde_json::Map<_, _>>();
-            Ok(ScopedJson::Derived(serde_json::Value::Object(collected)))
-        } else {
-            Ok(ScopedJson::Derived(serde_json::Value::Null))
-        }
+        regex_captures(h, default_cache()).map(ScopedJson::Derived)
     }
 }
 
+#[allow(non_camel_case_types)]
+struct regex_captures_cached_fct {
+    cache: Arc<RegexCache>,
+}
+
+impl HelperDef for regex_captures_cached_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        regex_captures(h, &self.cache).map(ScopedJson::Derived)
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct regex_captures_all_fct {
+    cache: Arc<RegexCache>,
+}
+
+impl HelperDef for regex_captures_all_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let on = find_str_hash(h, "regex_captures_all", "on")?;
+        let re = find_regex(h, "regex_captures_all", &self.cache)?;
+        let collected = re
+            .captures_iter(on)
+            .map(|caps| captures_to_json(&re, &caps))
+            .collect::<Vec<_>>();
+        Ok(ScopedJson::Derived(serde_json::Value::Array(collected)))
+    }
+}
+
+fn regex_is_match(h: &Helper, cache: &RegexCache) -> Result<serde_json::Value, RenderError> {
+    let on = find_str_hash(h, "regex_is_match", "on")?;
+    let re = find_regex(h, "regex_is_match", cache)?;
+    Ok(serde_json::Value::Bool(re.is_match(on)))
+}
+
+/// `regex_is_match` using a cache shared by the process (use [`register_with_cache`] to provide one)
 #[allow(non_camel_case_types)]
 pub struct regex_is_match_fct;
 
@@ -66,32 +370,210 @@ impl HelperDef for regex_is_match_fct {
         _: &'rc Context,
         _: &mut RenderContext<'reg, 'rc>,
     ) -> Result<ScopedJson<'reg>, RenderError> {
-        let on = h.hash_get("on").and_then(|v| v.value().as_str()).ok_or(
-            RenderErrorReason::ParamNotFoundForName("regex_is_match", "on".to_string()),
-        )?;
+        regex_is_match(h, default_cache()).map(ScopedJson::Derived)
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct regex_is_match_cached_fct {
+    cache: Arc<RegexCache>,
+}
+
+impl HelperDef for regex_is_match_cached_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        regex_is_match(h, &self.cache).map(ScopedJson::Derived)
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct regex_find_all_fct {
+    cache: Arc<RegexCache>,
+}
+
+impl HelperDef for regex_find_all_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let on = find_str_hash(h, "regex_find_all", "on")?;
+        let re = find_regex(h, "regex_find_all", &self.cache)?;
+        let collected = re
+            .find_iter(on)
+            .map(|m| serde_json::Value::String(m.as_str().to_string()))
+            .collect::<Vec<_>>();
+        Ok(ScopedJson::Derived(serde_json::Value::Array(collected)))
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct regex_split_fct {
+    cache: Arc<RegexCache>,
+}
+
+impl HelperDef for regex_split_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let on = find_str_hash(h, "regex_split", "on")?;
+        let re = find_regex(h, "regex_split", &self.cache)?;
+        let to_json = |s: &str| serde_json::Value::String(s.to_string());
+        let collected = match find_usize_hash(h, "regex_split", "limit")? {
+            Some(limit) => re.splitn(on, limit).map(to_json).collect::<Vec<_>>(),
+            None => re.split(on).map(to_json).collect::<Vec<_>>(),
+        };
+        Ok(ScopedJson::Derived(serde_json::Value::Array(collected)))
+    }
+}
+
+/// Replace the first match (or every match if `all`),
+/// `$name` and `${name}` in the replacement are expanded with the captured groups.
+fn regex_replace(
+    h: &Helper,
+    helper_name: &'static str,
+    cache: &RegexCache,
+    all: bool,
+) -> Result<String, RenderError> {
+    let on = find_str_hash(h, helper_name, "on")?;
+    let replacement = find_str_hash(h, helper_name, "replacement")?;
+    let re = find_regex(h, helper_name, cache)?;
+    let result = if all {
+        re.replace_all(on, replacement)
+    } else {
+        re.replace(on, replacement)
+    };
+    Ok(result.into_owned())
+}
+
+#[allow(non_camel_case_types)]
+struct regex_replace_fct {
+    cache: Arc<RegexCache>,
+}
+
+impl HelperDef for regex_replace_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let result = regex_replace(h, "regex_replace", &self.cache, false)?;
+        Ok(ScopedJson::Derived(serde_json::Value::String(result)))
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct regex_replace_all_fct {
+    cache: Arc<RegexCache>,
+}
+
+impl HelperDef for regex_replace_all_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let result = regex_replace(h, "regex_replace_all", &self.cache, true)?;
+        Ok(ScopedJson::Derived(serde_json::Value::String(result)))
+    }
+}
 
-        let pattern = h
-            .hash_get("pattern")
-            .and_then(|v| v.value().as_str())
-            .ok_or(RenderErrorReason::ParamNotFoundForName(
-                "regex_is_match",
-                "pattern".to_string(),
-            ))?;
-        let re = Regex::new(pattern).map_err(|err| crate::to_other_error(err.to_string()))?;
-        Ok(ScopedJson::Derived(serde_json::Value::Bool(
-            re.is_match(on),
-        )))
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "regex", feature: Some("regex");
+    "regex_captures"() [pattern: "string", pattern_name: "string", on: "string", flags: "string", size_limit: "integer", dfa_size_limit: "integer"] -> "object" {
+        r##"{{ json_to_str (regex_captures pattern="(?<first>\\w)(\\w)(?:\\w)\\w(?<last>\\w)" on="today") }}"## => r##"{"_0":"today","_1":"t","_2":"o","_3":"y","first":"t","last":"y"}"##
+    }
+    "regex_captures_all"() [pattern: "string", pattern_name: "string", on: "string", flags: "string", size_limit: "integer", dfa_size_limit: "integer"] -> "array" {
+        r##"{{ json_to_str (regex_captures_all pattern="(?<key>\\w+)=(\\w+)" on="a=1, b=2") }}"## => r##"[{"_0":"a=1","_1":"a","_2":"1","key":"a"},{"_0":"b=2","_1":"b","_2":"2","key":"b"}]"##
+    }
+    "regex_is_match"() [pattern: "string", pattern_name: "string", on: "string", flags: "string", size_limit: "integer", dfa_size_limit: "integer"] -> "boolean" {
+        r##"{{ regex_is_match pattern="^foo$" on="FOO" flags="i" }}"## => "true"
+    }
+    "regex_find_all"() [pattern: "string", pattern_name: "string", on: "string", flags: "string", size_limit: "integer", dfa_size_limit: "integer"] -> "array" {
+        r##"{{ json_to_str (regex_find_all pattern="\\d+" on="v1.22.333") }}"## => r##"["1","22","333"]"##
+    }
+    "regex_split"() [pattern: "string", pattern_name: "string", on: "string", limit: "integer", flags: "string", size_limit: "integer", dfa_size_limit: "integer"] -> "array" {
+        r##"{{ json_to_str (regex_split pattern="\\s*,\\s*" on="a , b,c" limit=2) }}"## => r##"["a","b,c"]"##
     }
+    "regex_replace"() [pattern: "string", pattern_name: "string", on: "string", replacement: "string", flags: "string", size_limit: "integer", dfa_size_limit: "integer"] -> "string" {
+        r##"{{ regex_replace pattern="\\d+" on="v1.22.333" replacement="x" }}"## => "vx.22.333"
+    }
+    "regex_replace_all"() [pattern: "string", pattern_name: "string", on: "string", replacement: "string", flags: "string", size_limit: "integer", dfa_size_limit: "integer"] -> "string" {
+        r##"{{ regex_replace_all pattern="(?<first>\\w+)_(?<last>\\w+)" on="foo_bar baz_qux" replacement="${last}_$first" }}"## => "bar_foo qux_baz"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with_cache(handlebars, Arc::new(RegexCache::default()))
 }
 
-pub fn register(handlebars: &mut Handlebars) {
-    handlebars.register_helper("regex_captures", Box::new(regex_captures_fct));
-    handlebars.register_helper("regex_is_match", Box::new(regex_is_match_fct));
+/// Register the regex helpers using the provided cache (eg to change the capacity, or to provide named patterns)
+pub fn register_with_cache(handlebars: &mut impl HelperRegistry, cache: Arc<RegexCache>) {
+    handlebars.register_helper(
+        "regex_captures",
+        Box::new(regex_captures_cached_fct {
+            cache: cache.clone(),
+        }),
+    );
+    handlebars.register_helper(
+        "regex_captures_all",
+        Box::new(regex_captures_all_fct {
+            cache: cache.clone(),
+        }),
+    );
+    handlebars.register_helper(
+        "regex_is_match",
+        Box::new(regex_is_match_cached_fct {
+            cache: cache.clone(),
+        }),
+    );
+    handlebars.register_helper(
+        "regex_find_all",
+        Box::new(regex_find_all_fct {
+            cache: cache.clone(),
+        }),
+    );
+    handlebars.register_helper(
+        "regex_split",
+        Box::new(regex_split_fct {
+            cache: cache.clone(),
+        }),
+    );
+    handlebars.register_helper(
+        "regex_replace",
+        Box::new(regex_replace_fct {
+            cache: cache.clone(),
+        }),
+    );
+    handlebars.register_helper(
+        "regex_replace_all",
+        Box::new(regex_replace_all_fct {
+            cache: cache.clone(),
+        }),
+    );
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::assert_renders;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
     use std::error::Error;
 
     #[test]
@@ -113,6 +595,24 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn test_regex_captures_all() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ json_to_str( regex_captures_all pattern="foo" on="" ) }}"##,
+                r##"[]"##
+            ),
+            (
+                r##"{{ json_to_str( regex_captures_all pattern="(?<key>\\w+)=(\\w+)" on="a=1, b=2" ) }}"##,
+                r##"[{"_0":"a=1","_1":"a","_2":"1","key":"a"},{"_0":"b=2","_1":"b","_2":"2","key":"b"}]"##
+            ),
+            (
+                r##"{{#each (regex_captures_all pattern="(?<key>\\w+)=(\\w+)" on="a=1, b=2" ) }}{{ this.key }}:{{ this._2 }};{{/each}}"##,
+                r##"a:1;b:2;"##
+            ),
+        ]
+    }
+
     #[test]
     fn test_regex_is_match() -> Result<(), Box<dyn Error>> {
         assert_renders![
@@ -130,4 +630,292 @@ mod tests {
             ),
         ]
     }
+
+    #[test]
+    fn test_regex_find_all() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ json_to_str( regex_find_all pattern="\\d+" on="" ) }}"##,
+                r##"[]"##
+            ),
+            (
+                r##"{{ json_to_str( regex_find_all pattern="\\d+" on="v1.22.333" ) }}"##,
+                r##"["1","22","333"]"##
+            ),
+            (
+                r##"{{#each (regex_find_all pattern="\\d+" on="v1.22.333" ) }}<{{ this }}>{{/each}}"##,
+                r##"<1><22><333>"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_regex_split() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ json_to_str( regex_split pattern="\\s*,\\s*" on="a , b,c" ) }}"##,
+                r##"["a","b","c"]"##
+            ),
+            (
+                r##"{{ json_to_str( regex_split pattern="\\s*,\\s*" on="a , b,c" limit=2 ) }}"##,
+                r##"["a","b,c"]"##
+            ),
+            (
+                r##"{{ json_to_str( regex_split pattern="," on="" ) }}"##,
+                r##"[""]"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_regex_replace() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ regex_replace_all pattern="\\d+" on="v1.22.333" replacement="x" }}"##,
+                r##"vx.x.x"##
+            ),
+            (
+                r##"{{ regex_replace_all pattern="get_(\\w+)" on="get_name get_id" replacement="$1" }}"##,
+                r##"name id"##
+            ),
+            (
+                r##"{{ regex_replace pattern="foo" on="bar" replacement="baz" }}"##,
+                r##"bar"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_regex_cache_reuse_compiled_pattern() -> Result<(), Box<dyn Error>> {
+        let cache = Arc::new(RegexCache::default());
+        let mut hbs = crate::new_hbs();
+        register_with_cache(&mut hbs, cache.clone());
+        let data = json!({"rows": (0..100).map(|i| format!("row{}", i)).collect::<Vec<_>>()});
+        let actual = hbs.render_template(
+            r##"{{#each rows}}{{#if (regex_is_match pattern="^row\\d*0$" on=this)}}{{this}} {{/if}}{{/each}}"##,
+            &data,
+        )?;
+        assert_eq!(
+            actual,
+            "row0 row10 row20 row30 row40 row50 row60 row70 row80 row90 "
+        );
+        assert_eq!(cache.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_unit_structs() -> Result<(), Box<dyn Error>> {
+        let mut hbs = handlebars::Handlebars::new();
+        hbs.register_helper("regex_captures", Box::new(regex_captures_fct));
+        hbs.register_helper("regex_is_match", Box::new(regex_is_match_fct));
+        assert_eq!(
+            hbs.render_template(
+                r##"{{#with (regex_captures pattern="(?<first>\\w)\\w*" on="today")}}{{first}}{{/with}} {{ regex_is_match pattern="^foo$" on="foo" }}"##,
+                &json!({}),
+            )?,
+            "t true"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_cache_is_bounded() -> Result<(), Box<dyn Error>> {
+        let cache = RegexCache::with_capacity(2);
+        let options = RegexOptions::default();
+        cache.get_or_compile("a", &options)?;
+        cache.get_or_compile("b", &options)?;
+        cache.get_or_compile("a", &options)?;
+        cache.get_or_compile("c", &options)?;
+        assert_eq!(cache.len(), 2);
+        // "b" is the least recently used, so it was evicted
+        let patterns = cache
+            .lock()
+            .compiled
+            .keys()
+            .map(|k| k.pattern.clone())
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(patterns, ["a", "c"].map(String::from).into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_cache_with_zero_capacity() -> Result<(), Box<dyn Error>> {
+        let cache = RegexCache::with_capacity(0);
+        assert!(cache
+            .get_or_compile("a", &RegexOptions::default())?
+            .is_match("bar"));
+        assert!(cache.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_named_pattern() -> Result<(), Box<dyn Error>> {
+        let cache = Arc::new(RegexCache::default());
+        cache.register_pattern("semver", r"(?<major>\d+)\.(?<minor>\d+)\.(?<patch>\d+)")?;
+        assert!(cache.register_pattern("invalid", "(").is_err());
+        let mut hbs = crate::new_hbs();
+        register_with_cache(&mut hbs, cache);
+        let data = json!({});
+        assert_eq!(
+            hbs.render_template(
+                r##"{{ set v=(regex_captures pattern_name="semver" on="v1.22.333") }}{{ v.minor }}"##,
+                &data
+            )?,
+            "22"
+        );
+        assert_eq!(
+            hbs.render_template(
+                r##"{{ regex_is_match pattern_name="semver" on="1.2" }}"##,
+                &data
+            )?,
+            "false"
+        );
+        assert!(hbs
+            .render_template(
+                r##"{{ regex_is_match pattern_name="unknown" on="1.2" }}"##,
+                &data
+            )
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_flags() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ regex_is_match pattern="^foo$" on="FOO" }}"##,
+                r##"false"##
+            ),
+            (
+                r##"{{ json_to_str( regex_find_all pattern="^\\w+$" on="foo\nbar" flags="m" ) }}"##,
+                r##"["foo","bar"]"##
+            ),
+            (
+                r##"{{ regex_is_match pattern="foo.bar" on="foo\nbar" }}"##,
+                r##"false"##
+            ),
+            (
+                r##"{{ regex_is_match pattern="foo.bar" on="foo\nbar" flags="s" }}"##,
+                r##"true"##
+            ),
+            (
+                r##"{{ regex_is_match pattern="f o o # comment" on="foo" flags="x" }}"##,
+                r##"true"##
+            ),
+            (
+                r##"{{ regex_replace pattern="a.+" on="aaa" replacement="x" flags="U" }}"##,
+                r##"xa"##
+            ),
+            (
+                r##"{{ regex_is_match pattern="^\\w$" on="é" }}"##,
+                r##"true"##
+            ),
+            (
+                r##"{{ regex_is_match pattern="^\\w$" on="é" flags="-u" }}"##,
+                r##"false"##
+            ),
+            (
+                r##"{{ regex_is_match pattern="^foo$" on="FOO" flags="mi" }}"##,
+                r##"true"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_regex_flags_are_part_of_the_cache_key() -> Result<(), Box<dyn Error>> {
+        let cache = Arc::new(RegexCache::default());
+        let mut hbs = crate::new_hbs();
+        register_with_cache(&mut hbs, cache.clone());
+        let actual = hbs.render_template(
+            r##"{{ regex_is_match pattern="foo" on="FOO" }} {{ regex_is_match pattern="foo" on="FOO" flags="i" }}"##,
+            &json!({}),
+        )?;
+        assert_eq!(actual, "false true");
+        assert_eq!(cache.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_invalid_flag() {
+        let hbs = crate::new_hbs();
+        let err = hbs
+            .render_template(
+                r##"{{ regex_is_match pattern="foo" on="foo" flags="iz" }}"##,
+                &json!({}),
+            )
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("unknown regex flag 'z'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_regex_size_limit() -> Result<(), Box<dyn Error>> {
+        let hbs = crate::new_hbs();
+        let err = hbs
+            .render_template(
+                r##"{{ regex_is_match pattern="\\w{100}" on="foo" size_limit=1000 }}"##,
+                &json!({}),
+            )
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("exceeds the size limit of 1000 bytes"),
+            "{}",
+            err
+        );
+        assert_eq!(
+            hbs.render_template(
+                r##"{{ regex_is_match pattern="\\w{3}" on="foo" size_limit=1000000 dfa_size_limit=1000000 }}"##,
+                &json!({}),
+            )?,
+            "true"
+        );
+        // a too small dfa_size_limit only slows down the search
+        assert_eq!(
+            hbs.render_template(
+                r##"{{ regex_is_match pattern="\\w{3}" on="foo" dfa_size_limit=1 }}"##,
+                &json!({}),
+            )?,
+            "true"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_options_wrong_type() {
+        let hbs = crate::new_hbs();
+        for (template, name) in [
+            (
+                r##"{{ regex_is_match pattern="a" on="a" size_limit="10" }}"##,
+                "size_limit",
+            ),
+            (
+                r##"{{ regex_is_match pattern="a" on="a" size_limit=-1 }}"##,
+                "size_limit",
+            ),
+            (
+                r##"{{ regex_is_match pattern="a" on="a" dfa_size_limit=true }}"##,
+                "dfa_size_limit",
+            ),
+            (
+                r##"{{ regex_is_match pattern="a" on="a" flags=1 }}"##,
+                "flags",
+            ),
+            (
+                r##"{{ regex_split pattern="a" on="a" limit="2" }}"##,
+                "limit",
+            ),
+        ] {
+            let err = hbs.render_template(template, &json!({})).unwrap_err();
+            assert!(
+                matches!(err.reason(), RenderErrorReason::HashTypeMismatchForName(_, n, _) if n == name),
+                "{}: {}",
+                template,
+                err
+            );
+        }
+    }
 }
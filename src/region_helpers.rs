@@ -1,16 +1,55 @@
 use handlebars::{
     Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, Renderable,
 };
-use log::warn;
+use log::Level;
 
-pub fn register(handlebars: &mut Handlebars) {
-    handlebars.register_helper("replace_section", Box::new(ReplaceSectionHelper))
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::ErrorPolicy;
+use crate::HelperRegistry;
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "region", feature: Some("jsontype");
+    "replace_section"(content: "string") [begin: "string", end: "string", remove_boundaries: "boolean" = "false"] -> "block" {
+        r##"{{#replace_section "a<!-- begin -->b<!-- end -->c" begin="<!-- begin -->" end="<!-- end -->" }}new{{/replace_section}}"## => "a<!-- begin -->new<!-- end -->c"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with_policy(handlebars, ErrorPolicy::default())
 }
 
+pub fn register_with_policy(handlebars: &mut impl HelperRegistry, policy: ErrorPolicy) {
+    handlebars.register_helper("replace_section", Box::new(replace_section_fct { policy }))
+}
+
+/// `replace_section` with the lenient policy, see [`register_with_policy`] for the strict one
 #[derive(Clone, Copy)]
 pub struct ReplaceSectionHelper;
 
 impl HelperDef for ReplaceSectionHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        hbs: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        replace_section_fct {
+            policy: ErrorPolicy::Lenient,
+        }
+        .call(h, hbs, ctx, rc, out)
+    }
+}
+
+/// If the section is not found (or a parameter is missing), emit nothing (lenient) or fail (strict)
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+struct replace_section_fct {
+    policy: ErrorPolicy,
+}
+
+impl HelperDef for replace_section_fct {
     fn call<'reg: 'rc, 'rc>(
         &self,
         h: &Helper<'rc>,
@@ -20,13 +59,19 @@ impl HelperDef for ReplaceSectionHelper {
         out: &mut dyn Output,
     ) -> HelperResult {
         let Some(tmpl) = h.template() else {
-            warn!("`replace_section` helper require a template");
-            return Ok(());
+            return self.policy.recover(
+                Level::Warn,
+                (),
+                "`replace_section` helper require a template".to_owned(),
+            );
         };
 
         let Some(input) = h.param(0).and_then(|it| it.value().as_str()) else {
-            warn!("`replace_section` helper require an string parameter");
-            return Ok(());
+            return self.policy.recover(
+                Level::Warn,
+                (),
+                "`replace_section` helper require an string parameter".to_owned(),
+            );
         };
 
         let remove_boundaries = h
@@ -35,21 +80,33 @@ impl HelperDef for ReplaceSectionHelper {
             .unwrap_or_default();
 
         let Some(begin) = h.hash_get("begin").and_then(|it| it.value().as_str()) else {
-            warn!("`replace_section` helper require a 'begin' string value");
-            return Ok(());
+            return self.policy.recover(
+                Level::Warn,
+                (),
+                "`replace_section` helper require a 'begin' string value".to_owned(),
+            );
         };
         let Some((before, inner)) = input.split_once(begin) else {
-            warn!("Begin region '{begin}' not found in '{input}'");
-            return Ok(());
+            return self.policy.recover(
+                Level::Warn,
+                (),
+                format!("Begin region '{begin}' not found in '{input}'"),
+            );
         };
 
         let Some(end) = h.hash_get("end").and_then(|it| it.value().as_str()) else {
-            warn!("`replace_section` helper require a 'end' string value ");
-            return Ok(());
+            return self.policy.recover(
+                Level::Warn,
+                (),
+                "`replace_section` helper require a 'end' string value ".to_owned(),
+            );
         };
         let Some((_, after)) = inner.split_once(end) else {
-            warn!("End region '{end}' not found in '{inner}'");
-            return Ok(());
+            return self.policy.recover(
+                Level::Warn,
+                (),
+                format!("End region '{end}' not found in '{inner}'"),
+            );
         };
 
         out.write(before)?;
@@ -73,7 +130,7 @@ mod tests {
     use serde_json::json;
     use similar_asserts::assert_eq;
 
-    use crate::new_hbs;
+    use crate::{new_hbs, ErrorPolicy};
 
     const INPUT: &str = r#"
 <!DOCTYPE html>
@@ -126,6 +183,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_helper_replace_section_not_found() -> Result<(), Box<dyn Error>> {
+        let data = json!({
+            "content": INPUT,
+        });
+        let tmpl = r#"{{#replace_section begin="<!-- #region foo-->" end="<!-- #endregion foo -->" content }}new{{/replace_section}}"#;
+        assert_eq!(new_hbs().render_template(tmpl, &data)?, "");
+
+        let mut hbs = handlebars::Handlebars::new();
+        crate::setup_handlebars_with_policy(&mut hbs, ErrorPolicy::Strict);
+        let err = hbs.render_template(tmpl, &data).unwrap_err();
+        assert!(err.to_string().contains("<!-- #region foo-->"), "{}", err);
+        let tmpl = r#"{{#replace_section begin="<!-- #region head-->" end="<!-- #endregion foo -->" content }}new{{/replace_section}}"#;
+        assert!(hbs.render_template(tmpl, &data).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_helper_replace_section_unit_struct() -> Result<(), Box<dyn Error>> {
+        let mut hbs = handlebars::Handlebars::new();
+        hbs.register_helper("replace_section", Box::new(super::ReplaceSectionHelper));
+        let tmpl = r#"{{#replace_section content begin="[" end="]" }}new{{/replace_section}}"#;
+        assert_eq!(
+            hbs.render_template(tmpl, &json!({ "content": "a[b]c" }))?,
+            "a[new]c"
+        );
+        assert_eq!(hbs.render_template(tmpl, &json!({ "content": "abc" }))?, "");
+        Ok(())
+    }
+
     #[test]
     fn test_helper_replace_section_remove_remove_boundaries() -> Result<(), Box<dyn Error>> {
         let data = json!({
@@ -1,8 +1,19 @@
+use crate::outputs::StringOutput;
 use cruet::Inflector;
 use handlebars::{
-    handlebars_helper, Context, Handlebars, Helper, HelperDef, RenderContext, RenderError,
-    ScopedJson,
+    handlebars_helper, Context, Handlebars, Helper, HelperDef, HelperResult, JsonRender, Output,
+    RenderContext, RenderError, RenderErrorReason, Renderable, ScopedJson,
 };
+use serde_json::Value as Json;
+use std::collections::HashSet;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::ErrorPolicy;
+use crate::HelperRegistry;
 
 #[macro_export]
 macro_rules! handlebars_register_cruet {
@@ -14,6 +25,8 @@ macro_rules! handlebars_register_cruet {
     }
 }
 
+/// Return the first non-empty string (other values are ignored),
+/// see `first_non_empty_value` in `logic_helpers` to keep the type of the value.
 #[allow(non_camel_case_types)]
 pub struct first_non_empty_fct;
 
@@ -40,7 +53,1223 @@ impl HelperDef for first_non_empty_fct {
     }
 }
 
-pub fn register(handlebars: &mut Handlebars) {
+/// Display width of the string (in columns), East-Asian wide characters and emoji count for 2
+pub fn display_width(s: &str) -> usize {
+    s.graphemes(true).map(UnicodeWidthStr::width).sum()
+}
+
+/// Max length of the output of `repeat` (in bytes), `pad_start`, `pad_end`, `center` (in columns),
+/// to protect against typo like `repeat "-" 1000000000`
+const OUTPUT_MAX_LEN: usize = 10_000_000;
+
+fn check_output_len(helper_name: &str, len: usize) -> Result<(), RenderError> {
+    if len > OUTPUT_MAX_LEN {
+        return Err(crate::to_other_error(format!(
+            "{}: output too long ({} > {})",
+            helper_name, len, OUTPUT_MAX_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Build a filler of `width` columns by repeating the graphemes of `pad`
+/// (completed by spaces when a wide grapheme doesn't fit)
+fn filler(pad: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut current = 0;
+    for g in pad.graphemes(true).filter(|g| g.width() > 0).cycle() {
+        if current + g.width() > width {
+            break;
+        }
+        result.push_str(g);
+        current += g.width();
+    }
+    result.push_str(&" ".repeat(width - current));
+    result
+}
+
+/// Position of the text inside the padding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Start,
+    End,
+    Center,
+}
+
+fn pad(s: &str, width: usize, pad: &str, align: Align) -> String {
+    let missing = width.saturating_sub(display_width(s));
+    let (before, after) = match align {
+        Align::Start => (0, missing),
+        Align::End => (missing, 0),
+        Align::Center => (missing / 2, missing - missing / 2),
+    };
+    format!("{}{}{}", filler(pad, before), s, filler(pad, after))
+}
+
+/// Keep the longest prefix (by grapheme) of `s` that fits into `width` columns
+fn take_width(s: &str, width: usize) -> &str {
+    let mut current = 0;
+    for (i, g) in s.grapheme_indices(true) {
+        current += g.width();
+        if current > width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+fn truncate(s: &str, width: usize, ellipsis: &str) -> String {
+    if display_width(s) <= width {
+        return s.to_owned();
+    }
+    let ellipsis = take_width(ellipsis, width);
+    let kept = take_width(s, width - display_width(ellipsis));
+    format!("{}{}", kept, ellipsis)
+}
+
+/// The indentation is a number of spaces or a prefix
+fn to_indent_prefix(helper_name: &str, v: &Json) -> Result<String, RenderError> {
+    match v {
+        Json::Number(n) => match n.as_u64() {
+            Some(n) => {
+                check_output_len(helper_name, usize::try_from(n).unwrap_or(usize::MAX))?;
+                Some(" ".repeat(n as usize))
+            }
+            None => None,
+        },
+        Json::String(s) => Some(s.to_owned()),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        crate::to_other_error(format!(
+            "indentation '{}' should be a number of spaces or a string",
+            v
+        ))
+    })
+}
+
+/// Prefix every line of `s` (blank lines are not prefixed when `skip_blank`)
+fn indent(
+    helper_name: &str,
+    s: &str,
+    prefix: &str,
+    skip_first: bool,
+    skip_blank: bool,
+) -> Result<String, RenderError> {
+    let lines = s.split_inclusive('\n').count();
+    check_output_len(
+        helper_name,
+        s.len().saturating_add(prefix.len().saturating_mul(lines)),
+    )?;
+    Ok(s.split_inclusive('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            if (skip_first && i == 0) || (skip_blank && line.trim().is_empty()) {
+                line.to_owned()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect())
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Remove the common leading whitespace of the non-blank lines (blank lines are emptied)
+fn dedent(s: &str) -> String {
+    let margin = s
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(leading_whitespace)
+        .reduce(|margin, ws| {
+            let common = margin
+                .bytes()
+                .zip(ws.bytes())
+                .take_while(|(a, b)| a == b)
+                .count();
+            &margin[..common]
+        })
+        .unwrap_or("");
+    s.split_inclusive('\n')
+        .map(|line| {
+            if line.trim().is_empty() {
+                line.trim_start_matches([' ', '\t'])
+            } else {
+                &line[margin.len()..]
+            }
+        })
+        .collect()
+}
+
+/// Word-wrap every line of `s` at `width` columns, the leading whitespace of a line is kept
+/// for each wrapped line, words longer than `width` are not split
+fn wrap(s: &str, width: usize) -> String {
+    s.split('\n')
+        .map(|line| {
+            let ws = leading_whitespace(line);
+            let width = width.saturating_sub(display_width(ws));
+            let mut result = ws.to_owned();
+            let mut current = 0;
+            for word in line.split_whitespace() {
+                let w = display_width(word);
+                if current > 0 && current + 1 + w > width {
+                    result.push('\n');
+                    result.push_str(ws);
+                    current = 0;
+                } else if current > 0 {
+                    result.push(' ');
+                    current += 1;
+                }
+                result.push_str(word);
+                current += w;
+            }
+            result
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `{{ indent 4 s }}`, written by the default [`HelperDef::call`] (so escaped like any other value)
+#[allow(non_camel_case_types)]
+struct indent_inline_fct;
+
+impl indent_inline_fct {
+    fn indent(&self, h: &Helper, s: &str) -> Result<String, RenderError> {
+        let prefix = to_indent_prefix(
+            "indent",
+            h.param(0)
+                .ok_or(RenderErrorReason::ParamNotFoundForIndex("indent", 0))?
+                .value(),
+        )?;
+        let skip_first = h
+            .hash_get("skip_first")
+            .and_then(|v| v.value().as_bool())
+            .unwrap_or(false);
+        indent("indent", s, &prefix, skip_first, true)
+    }
+}
+
+impl HelperDef for indent_inline_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let s = h
+            .param(1)
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("indent", 1))?
+            .value()
+            .render();
+        Ok(ScopedJson::Derived(Json::String(self.indent(h, &s)?)))
+    }
+}
+
+/// `{{ indent 4 s }}` or the block form `{{#indent 4}}...{{/indent}}`
+#[allow(non_camel_case_types)]
+pub struct indent_fct;
+
+impl HelperDef for indent_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        indent_inline_fct.call_inner(h, r, ctx, rc)
+    }
+
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        match h.template() {
+            Some(t) => {
+                let mut content = StringOutput::default();
+                t.render(r, ctx, rc, &mut content)?;
+                let res = indent_inline_fct.indent(h, &content.into_string()?)?;
+                out.write(&res).map_err(crate::to_nested_error)
+            }
+            None => indent_inline_fct.call(h, r, ctx, rc, out),
+        }
+    }
+}
+
+/// Convert a char index (negative from the end) into a char position in `0..=len`
+fn char_position(i: i64, len: usize) -> usize {
+    if i < 0 {
+        len.saturating_sub(i.unsigned_abs() as usize)
+    } else {
+        (i as usize).min(len)
+    }
+}
+
+/// Chars of `s` from `start` (included) to `end` (excluded, default to the end of `s`)
+fn substring(s: &str, start: i64, end: Option<i64>) -> String {
+    let len = s.chars().count();
+    let start = char_position(start, len);
+    let end = end.map(|e| char_position(e, len)).unwrap_or(len);
+    s.chars()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect()
+}
+
+/// The char at `index` (negative from the end), `null` if out of range
+fn char_at(s: &str, index: i64) -> Json {
+    let c = if index < 0 {
+        s.chars().nth_back((index.unsigned_abs() - 1) as usize)
+    } else {
+        s.chars().nth(index as usize)
+    };
+    c.map(|c| Json::String(c.to_string())).unwrap_or(Json::Null)
+}
+
+/// Char index of the first occurrence of `needle` in `s`, `-1` if not found
+fn index_of(s: &str, needle: &str) -> i64 {
+    s.find(needle)
+        .map(|i| s[..i].chars().count() as i64)
+        .unwrap_or(-1)
+}
+
+/// Normalized similarity between `a` and `b`, from `0` (nothing in common) to `1` (equal)
+fn similarity(a: &str, b: &str, algorithm: &str) -> Result<f64, RenderError> {
+    match algorithm {
+        "levenshtein" => Ok(strsim::normalized_levenshtein(a, b)),
+        "jaro_winkler" => Ok(strsim::jaro_winkler(a, b)),
+        _ => Err(crate::to_other_error(format!(
+            "similarity: algorithm '{}' unknown (expected: levenshtein, jaro_winkler)",
+            algorithm
+        ))),
+    }
+}
+
+/// The most similar candidate to `word` with a similarity >= `threshold`
+/// (the first one on equality), `null` if none
+fn closest(
+    word: &str,
+    candidates: &Json,
+    threshold: f64,
+    algorithm: &str,
+) -> Result<Json, RenderError> {
+    let candidates = match candidates {
+        Json::Array(items) => items.iter().map(|c| c.render()).collect(),
+        Json::String(s) => s.split(',').map(|c| c.trim().to_owned()).collect(),
+        Json::Null => vec![],
+        _ => {
+            return Err(crate::to_other_error(format!(
+                "closest: candidates '{}' is not an array or a string",
+                candidates
+            )))
+        }
+    };
+    let mut best: Option<(f64, String)> = None;
+    for candidate in candidates {
+        let score = similarity(word, &candidate, algorithm)?;
+        if score >= threshold && best.as_ref().is_none_or(|(s, _)| score > *s) {
+            best = Some((score, candidate));
+        }
+    }
+    Ok(best.map(|(_, c)| Json::String(c)).unwrap_or(Json::Null))
+}
+
+/// Transliterate to ASCII, then join the alphanumeric words with `separator`
+/// (truncated at `max_length` characters if not 0, without trailing separator)
+fn slugify(s: &str, separator: &str, max_length: usize) -> String {
+    let ascii = deunicode::deunicode(s).to_lowercase();
+    let mut slug = ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator);
+    // the byte offset of the first character after the limit (if longer)
+    let end = slug.char_indices().nth(max_length).map(|(end, _)| end);
+    if let Some(end) = end.filter(|_| max_length > 0) {
+        slug.truncate(end);
+        while !separator.is_empty() && slug.ends_with(separator) {
+            slug.truncate(slug.len() - separator.len());
+        }
+    }
+    slug
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Rust keywords that can not be used as raw identifiers (`r#self` is invalid)
+const RUST_NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+const JS_KEYWORDS: &[&str] = &[
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+const GO_KEYWORDS: &[&str] = &[
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "fallthrough",
+    "for",
+    "func",
+    "go",
+    "goto",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "type",
+    "var",
+];
+
+/// Build a valid (ASCII) identifier for the language `lang` (rust, js, python, go):
+/// transliterate, replace invalid characters by `_`, prefix by `_` if it starts with a digit,
+/// and escape the keywords (`r#type` for rust, `class_` for the others)
+fn to_identifier(s: &str, lang: &str) -> Result<String, RenderError> {
+    let keywords = match lang {
+        "rust" => RUST_KEYWORDS,
+        "js" => JS_KEYWORDS,
+        "python" => PYTHON_KEYWORDS,
+        "go" => GO_KEYWORDS,
+        _ => {
+            return Err(crate::to_other_error(format!(
+                "to_identifier: language '{}' unknown (expected: rust, js, python, go)",
+                lang
+            )))
+        }
+    };
+    let mut id = String::new();
+    for c in deunicode::deunicode(s).chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || (c == '$' && lang == "js") {
+            id.push(c);
+        } else if !id.ends_with('_') {
+            id.push('_');
+        }
+    }
+    if id.is_empty() || id.starts_with(|c: char| c.is_ascii_digit()) {
+        id.insert(0, '_');
+    }
+    if keywords.contains(&id.as_str()) {
+        if lang == "rust" && !RUST_NON_RAW_KEYWORDS.contains(&id.as_str()) {
+            id.insert_str(0, "r#");
+        } else {
+            id.push('_');
+        }
+    }
+    Ok(id)
+}
+
+/// Common initialisms of Go (from `golint`), written in uppercase
+const GO_INITIALISMS: &[&str] = &[
+    "ACL", "API", "ASCII", "CPU", "CSS", "DNS", "EOF", "GUID", "HTML", "HTTP", "HTTPS", "ID", "IP",
+    "JSON", "LHS", "QPS", "RAM", "RHS", "RPC", "SLA", "SMTP", "SQL", "SSH", "TCP", "TLS", "TTL",
+    "UDP", "UI", "UID", "UUID", "URI", "URL", "UTF8", "VM", "XML", "XMPP", "XSRF", "XSS",
+];
+
+/// Two-letter acronyms written in uppercase by the .NET naming guidelines (`IOStream`, `DBContext`)
+const DOTNET_ACRONYMS: &[&str] = &["DB", "IO", "IP", "OS", "UI", "VM"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Case {
+    Camel,
+    Pascal,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    Train,
+    Title,
+    Sentence,
+}
+
+impl Case {
+    const ALL: [Case; 8] = [
+        Case::Camel,
+        Case::Pascal,
+        Case::Snake,
+        Case::ScreamingSnake,
+        Case::Kebab,
+        Case::Train,
+        Case::Title,
+        Case::Sentence,
+    ];
+
+    fn helper_name(&self) -> &'static str {
+        match self {
+            Case::Camel => "to_camel_case",
+            Case::Pascal => "to_pascal_case",
+            Case::Snake => "to_snake_case",
+            Case::ScreamingSnake => "to_screaming_snake_case",
+            Case::Kebab => "to_kebab_case",
+            Case::Train => "to_train_case",
+            Case::Title => "to_title_case",
+            Case::Sentence => "to_sentence_case",
+        }
+    }
+
+    /// Conversion provided by cruet, used when no option is given (backward compatibility)
+    fn cruet(&self, s: &str) -> String {
+        match self {
+            Case::Camel => s.to_camel_case(),
+            Case::Pascal => s.to_pascal_case(),
+            Case::Snake => s.to_snake_case(),
+            Case::ScreamingSnake => s.to_screaming_snake_case(),
+            Case::Kebab => s.to_kebab_case(),
+            Case::Train => s.to_train_case(),
+            Case::Title => s.to_title_case(),
+            Case::Sentence => s.to_sentence_case(),
+        }
+    }
+
+    fn default_separator(&self) -> &'static str {
+        match self {
+            Case::Camel | Case::Pascal => "",
+            Case::Snake | Case::ScreamingSnake => "_",
+            Case::Kebab | Case::Train => "-",
+            Case::Title | Case::Sentence => " ",
+        }
+    }
+}
+
+/// Split `s` into words: on non-alphanumeric characters, before an uppercase following
+/// a lowercase or a digit (`userId`), and before the last uppercase of a run followed
+/// by a lowercase (`HTTPServer`)
+fn split_words(s: &str) -> Vec<String> {
+    let mut words = vec![];
+    for token in s.split(|c: char| !c.is_alphanumeric()) {
+        let chars = token.chars().collect::<Vec<_>>();
+        let mut word = String::new();
+        for (i, c) in chars.iter().enumerate() {
+            if i > 0 && c.is_uppercase() {
+                let prev = chars[i - 1];
+                let next_is_lower = chars.get(i + 1).map(|n| n.is_lowercase()).unwrap_or(false);
+                if prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next_is_lower)
+                {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            word.push(*c);
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Options of the case conversion
+#[derive(Debug, Clone, Default)]
+struct CaseOptions {
+    /// words written in uppercase (compared case-insensitively)
+    acronyms: Vec<String>,
+    separator: Option<String>,
+    /// .NET rule: acronyms longer than 2 letters are capitalized (`Http`, `Xml`)
+    dotnet: bool,
+}
+
+impl CaseOptions {
+    fn from_helper(h: &Helper) -> Result<Option<Self>, RenderError> {
+        let mut options = CaseOptions::default();
+        let mut is_set = false;
+        if let Some(preset) = h.hash_get("preset") {
+            is_set = true;
+            match preset.value().as_str().unwrap_or_default() {
+                "go" => options
+                    .acronyms
+                    .extend(GO_INITIALISMS.iter().map(|a| a.to_string())),
+                "dotnet" => {
+                    options.dotnet = true;
+                    options
+                        .acronyms
+                        .extend(DOTNET_ACRONYMS.iter().map(|a| a.to_string()))
+                }
+                preset => {
+                    return Err(crate::to_other_error(format!(
+                        "case preset '{}' unknown (expected: go, dotnet)",
+                        preset
+                    )))
+                }
+            }
+        }
+        if let Some(acronyms) = h.hash_get("acronyms") {
+            is_set = true;
+            match acronyms.value() {
+                Json::Array(items) => options
+                    .acronyms
+                    .extend(items.iter().filter_map(|v| v.as_str()).map(str::to_owned)),
+                Json::String(items) => options
+                    .acronyms
+                    .extend(items.split(',').map(|a| a.trim().to_owned())),
+                v => {
+                    return Err(crate::to_other_error(format!(
+                        "acronyms '{}' should be an array of strings",
+                        v
+                    )))
+                }
+            }
+        }
+        if let Some(separator) = h.hash_get("separator") {
+            is_set = true;
+            options.separator = Some(separator.value().render());
+        }
+        Ok(is_set.then_some(options))
+    }
+
+    fn is_acronym(&self, word: &str) -> bool {
+        self.acronyms.iter().any(|a| a.eq_ignore_ascii_case(word))
+    }
+
+    /// Capitalize the word, or write it in uppercase if it's an acronym
+    fn capitalize(&self, word: &str) -> String {
+        if self.is_acronym(word) && !(self.dotnet && word.chars().count() > 2) {
+            word.to_uppercase()
+        } else {
+            capitalize(word)
+        }
+    }
+}
+
+fn convert_case(s: &str, case: Case, options: &CaseOptions) -> String {
+    let words = split_words(s);
+    let words = words.iter().enumerate().map(|(i, word)| match case {
+        Case::Camel if i == 0 => word.to_lowercase(),
+        Case::Camel | Case::Pascal | Case::Train | Case::Title => options.capitalize(word),
+        Case::Sentence if i == 0 => options.capitalize(word),
+        Case::Sentence if options.is_acronym(word) => word.to_uppercase(),
+        Case::Sentence | Case::Snake | Case::Kebab => word.to_lowercase(),
+        Case::ScreamingSnake => word.to_uppercase(),
+    });
+    let separator = options
+        .separator
+        .as_deref()
+        .unwrap_or_else(|| case.default_separator());
+    words.collect::<Vec<_>>().join(separator)
+}
+
+/// Case conversion, with the options `acronyms=["HTTP", "ID"]`, `separator`, `preset="go"|"dotnet"`
+/// (without option the conversion of cruet is used)
+#[allow(non_camel_case_types)]
+struct case_fct {
+    case: Case,
+}
+
+impl HelperDef for case_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let helper_name = self.case.helper_name();
+        let v = h
+            .param(0)
+            .and_then(|v| v.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex(helper_name, 0))?;
+        let res = match CaseOptions::from_helper(h)? {
+            Some(options) => convert_case(v, self.case, &options),
+            None => self.case.cruet(v),
+        };
+        Ok(ScopedJson::Derived(Json::String(res)))
+    }
+}
+
+/// Custom inflection rules (irregular words, suffix rules, uncountable words) applied before
+/// the default rules of cruet by the helpers `to_plural`, `to_singular`, `to_class_case`,
+/// `to_table_case`, `is_class_case`, `is_table_case` and `pluralize`. `to_foreign_key` doesn't
+/// singularize nor pluralize (like the one of cruet), so it's not affected by the rules.
+///
+/// ```rust
+/// use handlebars_misc_helpers::string_helpers::Inflections;
+///
+/// let inflections = Inflections::new()
+///     .irregular("schema", "schemas")
+///     .plural_rule("ex", "exes")
+///     .singular_rule("exes", "ex")
+///     .uncountable("metadata");
+/// assert_eq!(inflections.pluralize("index"), "indexes");
+/// assert_eq!(inflections.singularize("Indexes"), "Index");
+/// assert_eq!(inflections.pluralize("metadata"), "metadata");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Inflections {
+    /// (singular, plural) in lowercase
+    irregulars: Vec<(String, String)>,
+    /// (suffix, replacement) in lowercase, the last added is tried first
+    plural_rules: Vec<(String, String)>,
+    singular_rules: Vec<(String, String)>,
+    uncountables: HashSet<String>,
+}
+
+impl Inflections {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an irregular word (in both directions), eg `("person", "people")`
+    pub fn irregular(mut self, singular: &str, plural: &str) -> Self {
+        self.irregulars
+            .push((singular.to_lowercase(), plural.to_lowercase()));
+        self
+    }
+
+    /// Add a rule to pluralize the words ending with `suffix`, eg `("ex", "exes")`
+    pub fn plural_rule(mut self, suffix: &str, replacement: &str) -> Self {
+        self.plural_rules
+            .push((suffix.to_lowercase(), replacement.to_lowercase()));
+        self
+    }
+
+    /// Add a rule to singularize the words ending with `suffix`, eg `("exes", "ex")`
+    pub fn singular_rule(mut self, suffix: &str, replacement: &str) -> Self {
+        self.singular_rules
+            .push((suffix.to_lowercase(), replacement.to_lowercase()));
+        self
+    }
+
+    /// Add a word that is the same in singular and plural, eg `"metadata"`
+    pub fn uncountable(mut self, word: &str) -> Self {
+        self.uncountables.insert(word.to_lowercase());
+        self
+    }
+
+    /// Apply the custom rules to get the plural (or the singular) of `word`,
+    /// `None` if no rule matches
+    fn apply(&self, word: &str, to_plural: bool) -> Option<String> {
+        let lower = word.to_lowercase();
+        if self.uncountables.contains(&lower) {
+            return Some(word.to_owned());
+        }
+        let rules = if to_plural {
+            &self.plural_rules
+        } else {
+            &self.singular_rules
+        };
+        let res = self
+            .irregulars
+            .iter()
+            .rev()
+            .find(|(singular, plural)| *singular == lower || *plural == lower)
+            .map(|(singular, plural)| if to_plural { plural } else { singular }.to_owned())
+            .or_else(|| {
+                rules.iter().rev().find_map(|(suffix, replacement)| {
+                    lower
+                        .strip_suffix(suffix.as_str())
+                        .map(|stem| format!("{}{}", stem, replacement))
+                })
+            })?;
+        // keep the capitalization of the first letter
+        Some(if word.starts_with(char::is_uppercase) {
+            capitalize_first(&res)
+        } else {
+            res
+        })
+    }
+
+    fn custom_plural(&self, word: &str) -> Option<String> {
+        self.apply(word, true)
+    }
+
+    fn custom_singular(&self, word: &str) -> Option<String> {
+        self.apply(word, false)
+    }
+
+    /// Plural of `word` with the custom rules, or the default rules of cruet
+    pub fn pluralize(&self, word: &str) -> String {
+        self.custom_plural(word).unwrap_or_else(|| word.to_plural())
+    }
+
+    /// Singular of `word` with the custom rules, or the default rules of cruet
+    pub fn singularize(&self, word: &str) -> String {
+        self.custom_singular(word)
+            .unwrap_or_else(|| word.to_singular())
+    }
+
+    /// Like `to_class_case` of cruet (the last word is singularized) with the custom rules
+    fn to_class_case(&self, s: &str) -> String {
+        let snake = s.to_snake_case();
+        let (head, last) = snake.rsplit_once('_').unwrap_or(("", &snake));
+        match self.custom_singular(last) {
+            Some(singular) => format!("{}_{}", head, singular).to_pascal_case(),
+            None => s.to_class_case(),
+        }
+    }
+
+    /// Like `to_table_case` of cruet (the last word is pluralized) with the custom rules
+    fn to_table_case(&self, s: &str) -> String {
+        let snake = s.to_snake_case();
+        match snake.rsplit_once('_') {
+            Some((head, last)) => match self.custom_plural(last) {
+                Some(plural) => format!("{}_{}", head, plural),
+                None => s.to_table_case(),
+            },
+            None => match self.custom_plural(&snake) {
+                Some(plural) => plural,
+                None => s.to_table_case(),
+            },
+        }
+    }
+}
+
+fn capitalize_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Inflection {
+    Plural,
+    Singular,
+    ClassCase,
+    TableCase,
+    IsClassCase,
+    IsTableCase,
+}
+
+impl Inflection {
+    const ALL: [Inflection; 6] = [
+        Inflection::Plural,
+        Inflection::Singular,
+        Inflection::ClassCase,
+        Inflection::TableCase,
+        Inflection::IsClassCase,
+        Inflection::IsTableCase,
+    ];
+
+    fn helper_name(&self) -> &'static str {
+        match self {
+            Inflection::Plural => "to_plural",
+            Inflection::Singular => "to_singular",
+            Inflection::ClassCase => "to_class_case",
+            Inflection::TableCase => "to_table_case",
+            Inflection::IsClassCase => "is_class_case",
+            Inflection::IsTableCase => "is_table_case",
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+struct inflection_fct {
+    inflection: Inflection,
+    inflections: Arc<Inflections>,
+}
+
+impl HelperDef for inflection_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let v = h.param(0).and_then(|v| v.value().as_str()).ok_or(
+            RenderErrorReason::ParamNotFoundForIndex(self.inflection.helper_name(), 0),
+        )?;
+        let res = match self.inflection {
+            Inflection::Plural => Json::String(self.inflections.pluralize(v)),
+            Inflection::Singular => Json::String(self.inflections.singularize(v)),
+            Inflection::ClassCase => Json::String(self.inflections.to_class_case(v)),
+            Inflection::TableCase => Json::String(self.inflections.to_table_case(v)),
+            Inflection::IsClassCase => Json::Bool(self.inflections.to_class_case(v) == v),
+            Inflection::IsTableCase => Json::Bool(self.inflections.to_table_case(v) == v),
+        };
+        Ok(ScopedJson::Derived(res))
+    }
+}
+
+/// `{{ pluralize count word }}`: the singular if count is 1 (or -1), else the plural
+#[allow(non_camel_case_types)]
+struct pluralize_fct {
+    inflections: Arc<Inflections>,
+}
+
+impl HelperDef for pluralize_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let count = h
+            .param(0)
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("pluralize", 0))?
+            .value();
+        let n = count
+            .as_f64()
+            .or_else(|| count.as_str().and_then(|s| s.trim().parse::<f64>().ok()))
+            .ok_or_else(|| {
+                crate::to_other_error(format!("pluralize: count '{}' is not a number", count))
+            })?;
+        let word = h
+            .param(1)
+            .and_then(|v| v.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("pluralize", 1))?;
+        let word = if n.abs() == 1.0 {
+            self.inflections.singularize(word)
+        } else {
+            self.inflections.pluralize(word)
+        };
+        let include_count = h
+            .hash_get("include_count")
+            .and_then(|v| v.value().as_bool())
+            .unwrap_or(false);
+        let res = if include_count {
+            format!("{} {}", count.render(), word)
+        } else {
+            word
+        };
+        Ok(ScopedJson::Derived(Json::String(res)))
+    }
+}
+
+/// `{{ unquote s }}`: if `s` is not quoted, return it as is (lenient) or fail (strict)
+#[allow(non_camel_case_types)]
+struct unquote_fct {
+    policy: ErrorPolicy,
+}
+
+impl HelperDef for unquote_fct {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg>, RenderError> {
+        let v = h
+            .param(0)
+            .and_then(|v| v.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("unquote", 0))?;
+        let res = match enquote::unquote(v) {
+            Ok(s) => s,
+            Err(e) => self.policy.recover(
+                log::Level::Warn,
+                v.to_owned(),
+                format!(
+                    "helper: unquote failed for string '{:?}' with error '{:?}'",
+                    v, e
+                ),
+            )?,
+        };
+        Ok(ScopedJson::Derived(Json::String(res)))
+    }
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "string", feature: Some("string");
+    "to_lower_case"(s: "string") -> "string" {
+        r##"{{ to_lower_case "Hello foo-bars" }}"## => "hello foo-bars"
+    }
+    "to_upper_case"(s: "string") -> "string" {
+        r##"{{ to_upper_case "Hello foo-bars" }}"## => "HELLO FOO-BARS"
+    }
+    "trim"(s: "string") -> "string" {
+        r##"{{ trim " foo " }}"## => "foo"
+    }
+    "trim_start"(s: "string") -> "string" {
+        r##"{{ trim_start " foo " }}"## => "foo "
+    }
+    "trim_end"(s: "string") -> "string" {
+        r##"{{ trim_end " foo " }}"## => " foo"
+    }
+    "replace"(s: "string", from: "string", to: "string") -> "string" {
+        r##"{{ replace "Hello old" "old" "new" }}"## => "Hello new"
+    }
+    "is_class_case"(s: "string") -> "boolean" {
+        r##"{{ is_class_case "FooBar" }}"## => "true"
+    }
+    "is_camel_case"(s: "string") -> "boolean" {
+        r##"{{ is_camel_case "fooBar" }}"## => "true"
+    }
+    "is_pascal_case"(s: "string") -> "boolean" {
+        r##"{{ is_pascal_case "FooBar" }}"## => "true"
+    }
+    "is_snake_case"(s: "string") -> "boolean" {
+        r##"{{ is_snake_case "foo_bar" }}"## => "true"
+    }
+    "is_screaming_snake_case"(s: "string") -> "boolean" {
+        r##"{{ is_screaming_snake_case "FOO_BAR" }}"## => "true"
+    }
+    "is_kebab_case"(s: "string") -> "boolean" {
+        r##"{{ is_kebab_case "foo-bar" }}"## => "true"
+    }
+    "is_train_case"(s: "string") -> "boolean" {
+        r##"{{ is_train_case "Foo-Bar" }}"## => "true"
+    }
+    "is_sentence_case"(s: "string") -> "boolean" {
+        r##"{{ is_sentence_case "Foo bar" }}"## => "true"
+    }
+    "is_title_case"(s: "string") -> "boolean" {
+        r##"{{ is_title_case "Foo Bar" }}"## => "true"
+    }
+    "is_table_case"(s: "string") -> "boolean" {
+        r##"{{ is_table_case "foo_bars" }}"## => "true"
+    }
+    "to_plural"(s: "string") -> "string" {
+        r##"{{ to_plural "Hello foo-bars" }}"## => "bars"
+    }
+    "to_singular"(s: "string") -> "string" {
+        r##"{{ to_singular "Hello foo-bars" }}"## => "bar"
+    }
+    "to_class_case"(s: "string") -> "string" {
+        r##"{{ to_class_case "Hello foo-bars" }}"## => "HelloFooBar"
+    }
+    "to_table_case"(s: "string") -> "string" {
+        r##"{{ to_table_case "Hello foo-bars" }}"## => "hello_foo_bars"
+    }
+    "pluralize"(count: "number|string", s: "string") [include_count: "boolean" = "false"] -> "string" {
+        r##"{{ pluralize 3 "person" include_count=true }}"## => "3 people"
+    }
+    "to_camel_case"(s: "string") [acronyms: "array|string", separator: "string", preset: "string"] -> "string" {
+        r##"{{ to_camel_case "user_id" preset="go" }}"## => "userID"
+    }
+    "to_pascal_case"(s: "string") [acronyms: "array|string", separator: "string", preset: "string"] -> "string" {
+        r##"{{ to_pascal_case "http_server" acronyms=["HTTP"] }}"## => "HTTPServer"
+    }
+    "to_snake_case"(s: "string") [acronyms: "array|string", separator: "string", preset: "string"] -> "string" {
+        r##"{{ to_snake_case "HTTPServerURL" separator="." }}"## => "http.server.url"
+    }
+    "to_screaming_snake_case"(s: "string") [acronyms: "array|string", separator: "string", preset: "string"] -> "string" {
+        r##"{{ to_screaming_snake_case "Hello foo-bars" }}"## => "HELLO_FOO_BARS"
+    }
+    "to_kebab_case"(s: "string") [acronyms: "array|string", separator: "string", preset: "string"] -> "string" {
+        r##"{{ to_kebab_case "Hello foo-bars" }}"## => "hello-foo-bars"
+    }
+    "to_train_case"(s: "string") [acronyms: "array|string", separator: "string", preset: "string"] -> "string" {
+        r##"{{ to_train_case "Hello foo-bars" }}"## => "Hello-Foo-Bars"
+    }
+    "to_title_case"(s: "string") [acronyms: "array|string", separator: "string", preset: "string"] -> "string" {
+        r##"{{ to_title_case "Hello foo-bars" }}"## => "Hello Foo Bars"
+    }
+    "to_sentence_case"(s: "string") [acronyms: "array|string", separator: "string", preset: "string"] -> "string" {
+        r##"{{ to_sentence_case "Hello foo-bars" }}"## => "Hello foo bars"
+    }
+    "deordinalize"(s: "string") -> "string" {
+        r##"{{ deordinalize "9th" }}"## => "9"
+    }
+    "ordinalize"(s: "string") -> "string" {
+        r##"{{ ordinalize "9" }}"## => "9th"
+    }
+    "is_foreign_key"(s: "string") -> "boolean" {
+        r##"{{ is_foreign_key "user_id" }}"## => "true"
+    }
+    "to_foreign_key"(s: "string") -> "string" {
+        r##"{{ to_foreign_key "Hello foo-bars" }}"## => "hello_foo_bars_id"
+    }
+    "deconstantize"(s: "string") -> "string" {
+        r##"{{ deconstantize "Test::Foo::Bar" }}"## => "Foo"
+    }
+    "demodulize"(s: "string") -> "string" {
+        r##"{{ demodulize "Test::Foo::Bar" }}"## => "Bar"
+    }
+    "quote"(symbol: "string", s: "string") -> "string" {
+        r##"{{ quote "'" "foo" }}"## => "'foo'"
+    }
+    "unquote"(s: "string") -> "string" {
+        r##"{{ unquote "\"foo\"" }}"## => "foo"
+    }
+    "first_non_empty"(values: "any+") -> "string" {
+        r##"{{ first_non_empty "" null "foo" "bar" }}"## => "foo"
+    }
+    "pad_start"(s: "string", width: "integer") [pad: "string" = " "] -> "string" {
+        r##"{{ pad_start "42" 5 pad="0" }}"## => "00042"
+    }
+    "pad_end"(s: "string", width: "integer") [pad: "string" = " "] -> "string" {
+        r##"{{ pad_end "日本" 6 }}"## => "日本  "
+    }
+    "center"(s: "string", width: "integer") [pad: "string" = " "] -> "string" {
+        r##"{{ center "foo" 7 pad="*" }}"## => "**foo**"
+    }
+    "truncate"(s: "string", width: "integer") [ellipsis: "string" = "…"] -> "string" {
+        r##"{{ truncate "foobar" 5 ellipsis="..." }}"## => "fo..."
+    }
+    "repeat"(s: "string", count: "integer") -> "string" {
+        r##"{{ repeat "-" 3 }}"## => "---"
+    }
+    "width"(s: "string") -> "integer" {
+        r##"{{ width "日本語" }}"## => "6"
+    }
+    "indent"(n: "integer|string", s: "string") [skip_first: "boolean" = "false"] -> "string" {
+        r##"{{ indent 2 "a\nb" }}"## => "  a\n  b"
+    }
+    "nindent"(n: "integer|string", s: "string") -> "string" {
+        r##"{{ nindent 2 "a\nb" }}"## => "\n  a\n  b"
+    }
+    "dedent"(s: "string") -> "string" {
+        r##"{{ dedent "  a\n    b" }}"## => "a\n  b"
+    }
+    "prefix_lines"(prefix: "string", s: "string") -> "string" {
+        r##"{{ prefix_lines "# " "a\n\nb" }}"## => "# a\n# \n# b"
+    }
+    "lines"(s: "string") -> "array" {
+        r##"{{#each (lines "a\r\nb\n") }}[{{ this }}]{{/each}}"## => "[a][b]"
+    }
+    "substring"(s: "string", start: "integer", end: "integer?") -> "string" {
+        r##"{{ substring "hello" -3 }}"## => "llo"
+    }
+    "char_at"(s: "string", index: "integer") -> "string" {
+        r##"{{ char_at "日本語" 1 }}"## => "本"
+    }
+    "index_of"(s: "string", needle: "string") -> "integer" {
+        r##"{{ index_of "日本語" "語" }}"## => "2"
+    }
+    "strip_prefix"(s: "string", prefix: "string") -> "string" {
+        r##"{{ strip_prefix "v1.2.3" "v" }}"## => "1.2.3"
+    }
+    "strip_suffix"(s: "string", suffix: "string") -> "string" {
+        r##"{{ strip_suffix "main.rs" ".rs" }}"## => "main"
+    }
+    "levenshtein"(a: "string", b: "string") -> "integer" {
+        r##"{{ levenshtein "kitten" "sitting" }}"## => "3"
+    }
+    "similarity"(a: "string", b: "string") [algorithm: "string" = "levenshtein"] -> "number" {
+        r##"{{ similarity "abcd" "abce" }}"## => "0.75"
+    }
+    "closest"(word: "string") [candidates: "array|string", threshold: "number" = "0.7", algorithm: "string" = "levenshtein"] -> "string" {
+        r##"{{ closest "comit" candidates=["checkout", "commit"] }}"## => "commit"
+    }
+    "to_ascii"(s: "string") -> "string" {
+        r##"{{ to_ascii "Café Überweisung" }}"## => "Cafe Uberweisung"
+    }
+    "slugify"(s: "string") [separator: "string" = "-", max_length: "integer" = "0"] -> "string" {
+        r##"{{ slugify "Café Überweisung" }}"## => "cafe-uberweisung"
+    }
+    "to_identifier"(s: "string", lang: "string") -> "string" {
+        r##"{{ to_identifier "type" "rust" }}"## => "r#type"
+    }
+    "unicode_nfc"(s: "string") -> "string" {
+        r##"{{ unicode_nfc "e\u0301" }}"## => "é"
+    }
+    "unicode_nfd"(s: "string") -> "string" {
+        r##"{{ unicode_nfd "é" }}"## => "e\u{301}"
+    }
+    "unicode_nfkc"(s: "string") -> "string" {
+        r##"{{ unicode_nfkc "ﬁ" }}"## => "fi"
+    }
+    "unicode_nfkd"(s: "string") -> "string" {
+        r##"{{ unicode_nfkd "ﬁ" }}"## => "fi"
+    }
+    "wrap"(s: "string", width: "integer") -> "string" {
+        r##"{{ wrap "the quick brown fox" 10 }}"## => "the quick\nbrown fox"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    register_with(
+        handlebars,
+        Arc::new(Inflections::default()),
+        ErrorPolicy::default(),
+    )
+}
+
+/// Register the string helpers using the provided custom inflection rules
+pub fn register_with_inflections(
+    handlebars: &mut impl HelperRegistry,
+    inflections: Arc<Inflections>,
+) {
+    register_with(handlebars, inflections, ErrorPolicy::default())
+}
+
+pub fn register_with_policy(handlebars: &mut impl HelperRegistry, policy: ErrorPolicy) {
+    register_with(handlebars, Arc::new(Inflections::default()), policy)
+}
+
+/// Register the string helpers using the provided custom inflection rules and error policy
+pub fn register_with(
+    handlebars: &mut impl HelperRegistry,
+    inflections: Arc<Inflections>,
+    policy: ErrorPolicy,
+) {
     {
         handlebars_helper!(to_lower_case: |v: str| v.to_lowercase());
         handlebars.register_helper("to_lower_case", Box::new(to_lower_case))
@@ -65,59 +1294,178 @@ pub fn register(handlebars: &mut Handlebars) {
         handlebars_helper!(replace: |v: str, from: str, to: str| v.replace(from, to));
         handlebars.register_helper("replace", Box::new(replace))
     }
-    handlebars_register_cruet!(handlebars, is_class_case);
-    handlebars_register_cruet!(handlebars, to_class_case);
     handlebars_register_cruet!(handlebars, is_camel_case);
-    handlebars_register_cruet!(handlebars, to_camel_case);
     handlebars_register_cruet!(handlebars, is_pascal_case);
-    handlebars_register_cruet!(handlebars, to_pascal_case);
     handlebars_register_cruet!(handlebars, is_snake_case);
-    handlebars_register_cruet!(handlebars, to_snake_case);
     handlebars_register_cruet!(handlebars, is_screaming_snake_case);
-    handlebars_register_cruet!(handlebars, to_screaming_snake_case);
     handlebars_register_cruet!(handlebars, is_kebab_case);
-    handlebars_register_cruet!(handlebars, to_kebab_case);
     handlebars_register_cruet!(handlebars, is_train_case);
-    handlebars_register_cruet!(handlebars, to_train_case);
     handlebars_register_cruet!(handlebars, is_sentence_case);
-    handlebars_register_cruet!(handlebars, to_sentence_case);
     handlebars_register_cruet!(handlebars, is_title_case);
-    handlebars_register_cruet!(handlebars, to_title_case);
-    handlebars_register_cruet!(handlebars, is_table_case);
-    handlebars_register_cruet!(handlebars, to_table_case);
+    for inflection in Inflection::ALL {
+        handlebars.register_helper(
+            inflection.helper_name(),
+            Box::new(inflection_fct {
+                inflection,
+                inflections: inflections.clone(),
+            }),
+        );
+    }
+    handlebars.register_helper("pluralize", Box::new(pluralize_fct { inflections }));
+    for case in Case::ALL {
+        handlebars.register_helper(case.helper_name(), Box::new(case_fct { case }));
+    }
     handlebars_register_cruet!(handlebars, deordinalize);
     handlebars_register_cruet!(handlebars, ordinalize);
     handlebars_register_cruet!(handlebars, is_foreign_key);
     handlebars_register_cruet!(handlebars, to_foreign_key);
     handlebars_register_cruet!(handlebars, deconstantize);
     handlebars_register_cruet!(handlebars, demodulize);
-    handlebars_register_cruet!(handlebars, to_plural);
-    handlebars_register_cruet!(handlebars, to_singular);
     {
         handlebars_helper!(quote: |quote_symbol: str, v: str| enquote::enquote(quote_symbol.chars().next().unwrap_or('"'), v));
         handlebars.register_helper("quote", Box::new(quote))
     }
+    handlebars.register_helper("unquote", Box::new(unquote_fct { policy }));
+    handlebars.register_helper("first_non_empty", Box::new(first_non_empty_fct));
     {
-        handlebars_helper!(unquote: |v: str| match enquote::unquote(v){
-            Err(e) => {
-                log::warn!(
-                    "helper: unquote failed for string '{:?}' with error '{:?}'",
-                    v, e
-                );
-                v.to_owned()
-            }
-            Ok(s) => s,
+        handlebars_helper!(pad_start: |v: str, width: u64, {pad: str = " "}| {
+            check_output_len("pad_start", width as usize)?;
+            self::pad(v, width as usize, pad, Align::End)
         });
-        handlebars.register_helper("unquote", Box::new(unquote))
+        handlebars.register_helper("pad_start", Box::new(pad_start))
+    }
+    {
+        handlebars_helper!(pad_end: |v: str, width: u64, {pad: str = " "}| {
+            check_output_len("pad_end", width as usize)?;
+            self::pad(v, width as usize, pad, Align::Start)
+        });
+        handlebars.register_helper("pad_end", Box::new(pad_end))
+    }
+    {
+        handlebars_helper!(center: |v: str, width: u64, {pad: str = " "}| {
+            check_output_len("center", width as usize)?;
+            self::pad(v, width as usize, pad, Align::Center)
+        });
+        handlebars.register_helper("center", Box::new(center))
+    }
+    {
+        handlebars_helper!(truncate_fct: |v: str, width: u64, {ellipsis: str = "…"}| truncate(v, width as usize, ellipsis));
+        handlebars.register_helper("truncate", Box::new(truncate_fct))
+    }
+    {
+        handlebars_helper!(repeat: |v: str, count: u64| {
+            check_output_len("repeat", v.len().saturating_mul(count as usize))?;
+            v.repeat(count as usize)
+        });
+        handlebars.register_helper("repeat", Box::new(repeat))
+    }
+    {
+        handlebars_helper!(width: |v: str| display_width(v));
+        handlebars.register_helper("width", Box::new(width))
+    }
+    handlebars.register_helper("indent", Box::new(indent_fct));
+    {
+        handlebars_helper!(nindent: |n: Json, v: str| format!("\n{}", indent("nindent", v, &to_indent_prefix("nindent", n)?, false, true)?));
+        handlebars.register_helper("nindent", Box::new(nindent))
+    }
+    {
+        handlebars_helper!(dedent_fct: |v: str| dedent(v));
+        handlebars.register_helper("dedent", Box::new(dedent_fct))
+    }
+    {
+        handlebars_helper!(prefix_lines: |prefix: str, v: str| indent("prefix_lines", v, prefix, false, false)?);
+        handlebars.register_helper("prefix_lines", Box::new(prefix_lines))
+    }
+    {
+        handlebars_helper!(lines: |v: str| v.lines().collect::<Vec<_>>());
+        handlebars.register_helper("lines", Box::new(lines))
+    }
+    {
+        handlebars_helper!(substring_fct: |v: str, start: i64, *args| {
+            let end = args
+                .get(2)
+                .map(|e| {
+                    e.as_i64().ok_or_else(|| {
+                        crate::to_other_error(format!("substring: '{}' is not an integer", e))
+                    })
+                })
+                .transpose()?;
+            substring(v, start, end)
+        });
+        handlebars.register_helper("substring", Box::new(substring_fct))
+    }
+    {
+        handlebars_helper!(char_at_fct: |v: str, index: i64| char_at(v, index));
+        handlebars.register_helper("char_at", Box::new(char_at_fct))
+    }
+    {
+        handlebars_helper!(index_of_fct: |v: str, needle: str| index_of(v, needle));
+        handlebars.register_helper("index_of", Box::new(index_of_fct))
+    }
+    {
+        handlebars_helper!(strip_prefix: |v: str, prefix: str| v.strip_prefix(prefix).unwrap_or(v));
+        handlebars.register_helper("strip_prefix", Box::new(strip_prefix))
+    }
+    {
+        handlebars_helper!(strip_suffix: |v: str, suffix: str| v.strip_suffix(suffix).unwrap_or(v));
+        handlebars.register_helper("strip_suffix", Box::new(strip_suffix))
+    }
+    {
+        handlebars_helper!(levenshtein: |a: str, b: str| strsim::levenshtein(a, b));
+        handlebars.register_helper("levenshtein", Box::new(levenshtein))
+    }
+    {
+        handlebars_helper!(similarity_fct: |a: str, b: str, {algorithm: str = "levenshtein"}| similarity(a, b, algorithm)?);
+        handlebars.register_helper("similarity", Box::new(similarity_fct))
+    }
+    {
+        handlebars_helper!(closest_fct: |word: str, {threshold: f64 = 0.7, algorithm: str = "levenshtein"}, **kwargs| {
+            let candidates = kwargs.get("candidates").copied().unwrap_or(&Json::Null);
+            closest(word, candidates, threshold, algorithm)?
+        });
+        handlebars.register_helper("closest", Box::new(closest_fct))
+    }
+    {
+        handlebars_helper!(to_ascii: |v: str| deunicode::deunicode(v));
+        handlebars.register_helper("to_ascii", Box::new(to_ascii))
+    }
+    {
+        handlebars_helper!(slugify_fct: |v: str, {separator: str = "-", max_length: u64 = 0}| slugify(v, separator, max_length as usize));
+        handlebars.register_helper("slugify", Box::new(slugify_fct))
+    }
+    {
+        handlebars_helper!(to_identifier_fct: |v: str, lang: str| to_identifier(v, lang)?);
+        handlebars.register_helper("to_identifier", Box::new(to_identifier_fct))
+    }
+    {
+        handlebars_helper!(unicode_nfc: |v: str| v.nfc().collect::<String>());
+        handlebars.register_helper("unicode_nfc", Box::new(unicode_nfc))
+    }
+    {
+        handlebars_helper!(unicode_nfd: |v: str| v.nfd().collect::<String>());
+        handlebars.register_helper("unicode_nfd", Box::new(unicode_nfd))
+    }
+    {
+        handlebars_helper!(unicode_nfkc: |v: str| v.nfkc().collect::<String>());
+        handlebars.register_helper("unicode_nfkc", Box::new(unicode_nfkc))
+    }
+    {
+        handlebars_helper!(unicode_nfkd: |v: str| v.nfkd().collect::<String>());
+        handlebars.register_helper("unicode_nfkd", Box::new(unicode_nfkd))
+    }
+    {
+        handlebars_helper!(wrap_fct: |v: str, width: u64| wrap(v, width as usize));
+        handlebars.register_helper("wrap", Box::new(wrap_fct))
     }
-    handlebars.register_helper("first_non_empty", Box::new(first_non_empty_fct));
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Inflections;
     use crate::assert_renders;
     use crate::tests::assert_helpers;
     use std::error::Error;
+    use std::sync::Arc;
 
     #[test]
     fn test_register_string_helpers() -> Result<(), Box<dyn Error>> {
@@ -149,7 +1497,6 @@ mod tests {
             (r##"{{ trim "foo" }}"##, r##"foo"##),
             (r##"{{ trim "  foo" }}"##, r##"foo"##),
             (r##"{{ trim "foo  " }}"##, r##"foo"##),
-            (r##"{{ trim " foo " }}"##, r##"foo"##)
         ]
     }
 
@@ -159,7 +1506,6 @@ mod tests {
             (r##"{{ trim_start "foo" }}"##, r##"foo"##),
             (r##"{{ trim_start "  foo" }}"##, r##"foo"##),
             (r##"{{ trim_start "foo  " }}"##, r##"foo  "##),
-            (r##"{{ trim_start " foo " }}"##, r##"foo "##)
         ]
     }
 
@@ -169,7 +1515,6 @@ mod tests {
             (r##"{{ trim_end "foo" }}"##, r##"foo"##),
             (r##"{{ trim_end "  foo" }}"##, r##"  foo"##),
             (r##"{{ trim_end "foo  " }}"##, r##"foo"##),
-            (r##"{{ trim_end " foo " }}"##, r##" foo"##)
         ]
     }
 
@@ -177,7 +1522,6 @@ mod tests {
     fn test_helper_quote() -> Result<(), Box<dyn Error>> {
         assert_renders![
             (r##"{{ quote "'" "''" }}"##, r##"'\'\''"##),
-            (r##"{{ quote "'" "foo" }}"##, r##"'foo'"##),
             (r##"{{ quote "\"" "foo" }}"##, r##""foo""##),
             (r##"{{ quote "" "foo" }}"##, r##""foo""##),
         ]
@@ -190,12 +1534,26 @@ mod tests {
             (r##"{{ unquote "'f'" }}"##, r##"f"##),
             (r##"{{ unquote "foo" }}"##, r##"foo"##),
             (r##"{{ unquote "'foo'" }}"##, r##"foo"##),
-            (r##"{{ unquote "\"foo\"" }}"##, r##"foo"##),
             (r##"{{ unquote "foo'" }}"##, r##"foo'"##),
             (r##"{{ unquote "'foo" }}"##, r##"'foo"##),
         ]
     }
 
+    #[test]
+    fn test_helper_unquote_strict() -> Result<(), Box<dyn Error>> {
+        let mut hbs = handlebars::Handlebars::new();
+        crate::setup_handlebars_with_policy(&mut hbs, crate::ErrorPolicy::Strict);
+        let data = serde_json::json!({});
+        assert_eq!(
+            hbs.render_template(r##"{{ unquote "'foo'" }}"##, &data)?,
+            "foo"
+        );
+        for tmpl in [r##"{{ unquote "foo" }}"##, r##"{{ unquote "'foo" }}"##] {
+            assert!(hbs.render_template(tmpl, &data).is_err(), "{}", tmpl);
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_helper_replace() -> Result<(), Box<dyn Error>> {
         assert_renders![(r##"{{ replace "foo" "oo" "aa"}}"##, r##"faa"##)]
@@ -214,4 +1572,504 @@ mod tests {
             (r##"{{ first_non_empty "" null "foo" "bar"}}"##, r##"foo"##),
         ]
     }
+
+    #[test]
+    fn test_helper_width() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ width "" }}"##, r##"0"##),
+            (r##"{{ width "foo" }}"##, r##"3"##),
+            (r##"{{ width "héllo" }}"##, r##"5"##),
+            (r##"{{ width "héllo" }}"##, r##"5"##),
+            (r##"{{ width "👍" }}"##, r##"2"##),
+        ]
+    }
+
+    #[test]
+    fn test_helper_pad() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"[{{ pad_start "foo" 6 }}]"##, r##"[   foo]"##),
+            (r##"[{{ pad_end "foo" 6 }}]"##, r##"[foo   ]"##),
+            (r##"[{{ center "foo" 8 }}]"##, r##"[  foo   ]"##),
+            (r##"[{{ pad_start "foo" 2 }}]"##, r##"[foo]"##),
+            (r##"[{{ pad_end "foo" 8 pad="-=" }}]"##, r##"[foo-=-=-]"##),
+            (r##"[{{ pad_start "é" 3 pad="." }}]"##, r##"[..é]"##),
+            (r##"[{{ pad_end "a" 4 pad="日" }}]"##, r##"[a日 ]"##),
+        ]
+    }
+
+    #[test]
+    fn test_helper_truncate() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ truncate "foobar" 6 }}"##, r##"foobar"##),
+            (r##"{{ truncate "foobar" 4 }}"##, r##"foo…"##),
+            (r##"{{ truncate "foobar" 3 ellipsis="" }}"##, r##"foo"##),
+            (r##"{{ truncate "foobar" 2 ellipsis="..." }}"##, r##".."##),
+            (r##"{{ truncate "日本語です" 5 }}"##, r##"日本…"##),
+            (r##"{{ truncate "日本語です" 6 }}"##, r##"日本…"##),
+        ]
+    }
+
+    #[test]
+    fn test_helper_repeat() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ repeat "ab" 3 }}"##, r##"ababab"##),
+            (r##"{{ repeat "ab" 0 }}"##, r##""##),
+            (r##"{{ repeat "-" (width "日本語") }}"##, r##"------"##),
+            (r##"{{ len (repeat "" 1000000000) }}"##, r##"0"##),
+        ]
+    }
+
+    #[test]
+    fn test_helper_output_max_len() {
+        let hbs = crate::new_hbs();
+        for tmpl in [
+            r##"{{ repeat "ab" 1000000000 }}"##,
+            r##"{{ repeat "ab" 18446744073709551615 }}"##,
+            r##"{{ pad_start "ab" 1000000000 }}"##,
+            r##"{{ pad_end "ab" 1000000000 }}"##,
+            r##"{{ center "ab" 1000000000 }}"##,
+            r##"{{ indent 100000000000 "a" }}"##,
+            r##"{{ nindent 100000000000 "a" }}"##,
+            r##"{{ indent 9000000 (repeat "a\n" 10) }}"##,
+            r##"{{#indent 100000000000}}a{{/indent}}"##,
+        ] {
+            let err = hbs.render_template(tmpl, &()).unwrap_err();
+            assert!(err.to_string().contains("too long"), "{}: {}", tmpl, err);
+        }
+    }
+
+    #[test]
+    fn test_helper_indent() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ indent 2 "a\n\nb\n" }}"##, "  a\n\n  b\n"),
+            (r##"{{ indent "> " "a\nb" }}"##, "> a\n> b"),
+            (r##"{{ indent 2 "a\nb" skip_first=true }}"##, "a\n  b"),
+            (r##"{{ indent 2 "" }}"##, ""),
+            (
+                r##"key:{{ nindent 2 "a: 1\nb: 2" }}"##,
+                "key:\n  a: 1\n  b: 2"
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_indent_block() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                "{{#indent 4}}a:\n  b: {{ to_upper_case \"c\" }}\n{{/indent}}",
+                "    a:\n      b: C\n"
+            ),
+            (
+                "items:{{#indent 2 skip_first=true}}\n- a\n- b{{/indent}}",
+                "items:\n  - a\n  - b"
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_indent_escape() -> Result<(), Box<dyn Error>> {
+        let mut hbs = crate::new_hbs();
+        hbs.register_escape_fn(handlebars::html_escape);
+        let data = serde_json::json!({});
+        assert_eq!(
+            hbs.render_template(r##"{{ indent 2 "<a>\n&" }}"##, &data)?,
+            "  &lt;a&gt;\n  &amp;"
+        );
+        assert_eq!(
+            hbs.render_template(r##"{{{ indent 2 "<a>\n&" }}}"##, &data)?,
+            "  <a>\n  &"
+        );
+        assert_eq!(
+            hbs.render_template(r##"{{#indent 2}}<a>{{/indent}}"##, &data)?,
+            "  <a>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_helper_dedent() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ dedent "  a\n    b\n  c" }}"##, "a\n  b\nc"),
+            (r##"{{ dedent "  a\n   \n  b\n" }}"##, "a\n\nb\n"),
+            (r##"{{ dedent "a\n  b" }}"##, "a\n  b"),
+            (
+                r##"{{ indent 4 (dedent "  a\n    b") }}"##,
+                "    a\n      b"
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_wrap() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ wrap "the quick brown fox jumps over the lazy dog" 10 }}"##,
+                "the quick\nbrown fox\njumps over\nthe lazy\ndog"
+            ),
+            (r##"{{ wrap "a verylongword b" 5 }}"##, "a\nverylongword\nb"),
+            (r##"{{ wrap "a b\n\nc d" 3 }}"##, "a b\n\nc d"),
+            (r##"{{ wrap "  a b c" 5 }}"##, "  a b\n  c"),
+            (r##"{{ wrap "日本 語です" 5 }}"##, "日本\n語です"),
+        ]
+    }
+
+    #[test]
+    fn test_helper_to_ascii_slugify() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ to_ascii "Æneid – œuvre" }}"##,
+                r##"AEneid - oeuvre"##
+            ),
+            (
+                r##"{{ slugify "  Hello, World!! 2024 " }}"##,
+                r##"hello-world-2024"##
+            ),
+            (
+                r##"{{ slugify "Café Überweisung" separator="_" }}"##,
+                r##"cafe_uberweisung"##
+            ),
+            (
+                r##"{{ slugify "Café Überweisung" max_length=5 }}"##,
+                r##"cafe"##
+            ),
+            (
+                r##"{{ slugify "Café Überweisung" max_length=7 }}"##,
+                r##"cafe-ub"##
+            ),
+            (r##"{{ slugify "!!!" }}"##, r##""##),
+            (
+                r##"{{ slugify "a b c" separator="–" max_length=2 }}"##,
+                r##"a"##
+            ),
+            (
+                r##"{{ slugify "ab cd" separator="–" max_length=4 }}"##,
+                r##"ab–c"##
+            ),
+            (
+                r##"{{ slugify "ab cd" separator="–" max_length=5 }}"##,
+                r##"ab–cd"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_unicode_normalization() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ len (unicode_nfc "é") }}"##, r##"2"##),
+            (r##"{{ len (unicode_nfc "é") }}"##, r##"2"##),
+            (r##"{{ len (unicode_nfd "é") }}"##, r##"3"##),
+            (r##"{{ unicode_nfkc "ﬁ①" }}"##, r##"fi1"##),
+            (
+                r##"{{ eq (unicode_nfc "é") (unicode_nfc "é") }}"##,
+                r##"true"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_to_identifier() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ to_identifier "Café Überweisung" "rust" }}"##,
+                r##"Cafe_Uberweisung"##
+            ),
+            (r##"{{ to_identifier "2fa-code" "go" }}"##, r##"_2fa_code"##),
+            (r##"{{ to_identifier "" "js" }}"##, r##"_"##),
+            (r##"{{ to_identifier "$el" "js" }}"##, r##"$el"##),
+            (r##"{{ to_identifier "$el" "python" }}"##, r##"_el"##),
+            (r##"{{ to_identifier "self" "rust" }}"##, r##"self_"##),
+            (r##"{{ to_identifier "class" "js" }}"##, r##"class_"##),
+            (r##"{{ to_identifier "None" "python" }}"##, r##"None_"##),
+            (r##"{{ to_identifier "none" "python" }}"##, r##"none"##),
+            (r##"{{ to_identifier "func" "go" }}"##, r##"func_"##),
+            (
+                r##"{{ to_identifier (to_snake_case "Max Size") "rust" }}"##,
+                r##"max_size"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_case_with_acronyms() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ to_pascal_case "HTTPServer" acronyms=["HTTP"] }}"##,
+                r##"HTTPServer"##
+            ),
+            (
+                r##"{{ to_camel_case "user_id" acronyms=["ID"] }}"##,
+                r##"userID"##
+            ),
+            (
+                r##"{{ to_camel_case "HTTPServer" acronyms=["HTTP"] }}"##,
+                r##"httpServer"##
+            ),
+            (
+                r##"{{ to_snake_case "userID" acronyms=["ID"] }}"##,
+                r##"user_id"##
+            ),
+            (
+                r##"{{ to_snake_case "HTTPServerURL" acronyms=[] }}"##,
+                r##"http_server_url"##
+            ),
+            (
+                r##"{{ to_screaming_snake_case "parseHTTPResponse" acronyms=[] }}"##,
+                r##"PARSE_HTTP_RESPONSE"##
+            ),
+            (
+                r##"{{ to_train_case "api-key" acronyms="API" }}"##,
+                r##"API-Key"##
+            ),
+            (
+                r##"{{ to_title_case "json api" acronyms=["JSON", "API"] }}"##,
+                r##"JSON API"##
+            ),
+            (
+                r##"{{ to_sentence_case "the json_api" acronyms=["JSON", "API"] }}"##,
+                r##"The JSON API"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_case_with_separator() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ to_snake_case "Hello foo-bars" separator="." }}"##,
+                r##"hello.foo.bars"##
+            ),
+            (
+                r##"{{ to_pascal_case "hello foo" separator="_" }}"##,
+                r##"Hello_Foo"##
+            ),
+            (
+                r##"{{ to_kebab_case "v2Api" separator="--" }}"##,
+                r##"v2--api"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_case_with_preset() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ to_pascal_case "user_id" preset="go" }}"##,
+                r##"UserID"##
+            ),
+            (
+                r##"{{ to_camel_case "serve-http" preset="go" }}"##,
+                r##"serveHTTP"##
+            ),
+            (
+                r##"{{ to_pascal_case "xml_http_request" preset="go" }}"##,
+                r##"XMLHTTPRequest"##
+            ),
+            (
+                r##"{{ to_pascal_case "xml_http_request" preset="dotnet" }}"##,
+                r##"XmlHttpRequest"##
+            ),
+            (
+                r##"{{ to_pascal_case "io_stream" preset="dotnet" }}"##,
+                r##"IOStream"##
+            ),
+            (
+                r##"{{ to_pascal_case "user_id" preset="dotnet" }}"##,
+                r##"UserId"##
+            ),
+            (
+                r##"{{ to_pascal_case "my_tcp_db" preset="dotnet" acronyms=["TCP"] }}"##,
+                r##"MyTcpDB"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_case_without_option_is_unchanged() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ to_pascal_case "HTTPServer" }}"##, r##"Httpserver"##),
+            (r##"{{ to_camel_case "userID" }}"##, r##"userID"##),
+            (
+                r##"{{ to_snake_case "Hello foo-bars" }}"##,
+                r##"hello_foo_bars"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_case_invalid_preset() {
+        let hbs = crate::new_hbs();
+        let data = serde_json::json!({});
+        assert!(hbs
+            .render_template(r##"{{ to_pascal_case "a" preset="java" }}"##, &data)
+            .is_err());
+    }
+
+    #[test]
+    fn test_helper_pluralize() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ pluralize 0 "item" }}"##, r##"items"##),
+            (r##"{{ pluralize 1 "item" }}"##, r##"item"##),
+            (r##"{{ pluralize 2 "item" }}"##, r##"items"##),
+            (r##"{{ pluralize 1 "items" }}"##, r##"item"##),
+            (r##"{{ pluralize "3" "person" }}"##, r##"people"##),
+            (r##"{{ pluralize 1.5 "day" }}"##, r##"days"##),
+            (
+                r##"{{ pluralize 3 "item" include_count=true }}"##,
+                r##"3 items"##
+            ),
+            (
+                r##"{{ pluralize 1 "item" include_count=true }}"##,
+                r##"1 item"##
+            ),
+            (
+                r##"{{ pluralize (len [1, 2]) "file" include_count=true }}"##,
+                r##"2 files"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_custom_inflections() -> Result<(), Box<dyn Error>> {
+        let inflections = Inflections::new()
+            .irregular("schema", "schemas")
+            .plural_rule("ex", "exes")
+            .singular_rule("exes", "ex")
+            .uncountable("metadata");
+        let mut hbs = crate::new_hbs();
+        super::register_with_inflections(&mut hbs, Arc::new(inflections));
+        let data = serde_json::json!({});
+        for (tmpl, expected) in [
+            (r##"{{ to_plural "schema" }}"##, "schemas"),
+            (r##"{{ to_singular "schemas" }}"##, "schema"),
+            (r##"{{ to_plural "Schema" }}"##, "Schemas"),
+            (r##"{{ to_plural "index" }}"##, "indexes"),
+            (r##"{{ to_singular "indexes" }}"##, "index"),
+            (r##"{{ to_plural "metadata" }}"##, "metadata"),
+            (r##"{{ to_plural "person" }}"##, "people"),
+            (r##"{{ pluralize 2 "schema" }}"##, "schemas"),
+            (r##"{{ pluralize 1 "indexes" }}"##, "index"),
+            (r##"{{ to_table_case "SearchIndex" }}"##, "search_indexes"),
+            (r##"{{ to_class_case "search_schemas" }}"##, "SearchSchema"),
+            (r##"{{ to_table_case "Hello foo-bar" }}"##, "hello_foo_bars"),
+            (r##"{{ is_table_case "search_indexes" }}"##, "true"),
+            (r##"{{ is_table_case "search_schemata" }}"##, "false"),
+            (r##"{{ is_class_case "SearchSchema" }}"##, "true"),
+            (r##"{{ is_class_case "SearchSchemas" }}"##, "false"),
+            (
+                r##"{{ to_foreign_key "SearchIndexes" }}"##,
+                "search_indexes_id",
+            ),
+        ] {
+            assert_eq!(hbs.render_template(tmpl, &data)?, expected, "{}", tmpl);
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_helper_lines() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ json_to_str (lines "a\nb\r\n\nc\n") }}"##,
+                r##"["a","b","","c"]"##
+            ),
+            (r##"{{ json_to_str (lines "") }}"##, r##"[]"##),
+            (
+                r##"{{#each (lines "x\ny") }}[{{ this }}]{{/each}}"##,
+                r##"[x][y]"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_substring() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ substring "hello" 1 }}"##, r##"ello"##),
+            (r##"{{ substring "hello" 1 3 }}"##, r##"el"##),
+            (r##"{{ substring "hello" 0 -1 }}"##, r##"hell"##),
+            (r##"{{ substring "hello" 3 1 }}"##, r##""##),
+            (r##"{{ substring "hello" 2 100 }}"##, r##"llo"##),
+            (r##"{{ substring "日本語です" 1 3 }}"##, r##"本語"##),
+            (r##"{{ char_at "hello" -1 }}"##, r##"o"##),
+            (r##"{{ char_at "hello" 5 }}"##, r##""##),
+            (r##"{{ char_at "hello" -6 }}"##, r##""##),
+            (r##"{{ index_of "hello" "l" }}"##, r##"2"##),
+            (r##"{{ index_of "hello" "z" }}"##, r##"-1"##),
+            (r##"{{ index_of "hello" "" }}"##, r##"0"##),
+            (
+                r##"{{#if (eq (index_of "hello" "z") -1) }}none{{/if}}"##,
+                r##"none"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_strip_prefix_suffix() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ strip_prefix "1.2.3" "v" }}"##, r##"1.2.3"##),
+            (r##"{{ strip_prefix "vv1" "v" }}"##, r##"v1"##),
+            (r##"{{ strip_suffix "main.rs" ".md" }}"##, r##"main.rs"##),
+        ]
+    }
+
+    #[test]
+    fn test_helper_levenshtein_similarity() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (r##"{{ levenshtein "" "abc" }}"##, r##"3"##),
+            (r##"{{ levenshtein "日本" "日本語" }}"##, r##"1"##),
+            (r##"{{ similarity "commit" "commit" }}"##, r##"1.0"##),
+            (r##"{{ similarity "abc" "xyz" }}"##, r##"0.0"##),
+            (
+                r##"{{ similarity "martha" "marhta" algorithm="jaro_winkler" }}"##,
+                r##"0.9611111111111111"##
+            ),
+            (
+                r##"{{#if (gt (similarity "comit" "commit") 0.8) }}close{{/if}}"##,
+                r##"close"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_closest() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ closest "comit" candidates=["checkout", "commit", "config"] }}"##,
+                r##"commit"##
+            ),
+            (
+                r##"{{ closest "stats" candidates="status, stash, start" }}"##,
+                r##"status"##
+            ),
+            (
+                r##"{{ closest "xyz" candidates=["checkout", "commit"] }}"##,
+                r##""##
+            ),
+            (
+                r##"{{ closest "xyz" candidates=["checkout", "commit"] threshold=0 }}"##,
+                r##"checkout"##
+            ),
+            (
+                r##"{{ closest "chekout" candidates=["checkout", "commit"] algorithm="jaro_winkler" threshold=0.9 }}"##,
+                r##"checkout"##
+            ),
+            (r##"{{ closest "comit" }}"##, r##""##),
+            (
+                r##"{{#with (closest "comit" candidates=["commit"]) }}did you mean '{{ this }}'?{{/with}}"##,
+                r##"did you mean 'commit'?"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_helper_similarity_invalid() {
+        let hbs = crate::new_hbs();
+        let data = serde_json::json!({});
+        for tmpl in [
+            r##"{{ similarity "a" "b" algorithm="soundex" }}"##,
+            r##"{{ closest "a" candidates=42 }}"##,
+        ] {
+            assert!(hbs.render_template(tmpl, &data).is_err(), "{}", tmpl);
+        }
+    }
 }
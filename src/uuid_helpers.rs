@@ -1,45 +1,242 @@
-use handlebars::{Context, Handlebars, Helper, HelperResult, Output, RenderContext};
-use uuid::Uuid;
-
-fn uuid_new_v4_fct(
-    _: &Helper,
-    _: &Handlebars,
-    _: &Context,
-    _: &mut RenderContext,
-    out: &mut dyn Output,
-) -> HelperResult {
-    out.write(Uuid::new_v4().to_string().as_str())?;
-    Ok(())
+use handlebars::handlebars_helper;
+use serde_json::{json, Value as Json};
+use uuid::{Uuid, Variant};
+
+use crate::catalog::{helpers_info, HelperInfo};
+use crate::HelperRegistry;
+
+fn format_uuid(
+    uuid: Uuid,
+    format: &str,
+    uppercase: bool,
+) -> Result<String, handlebars::RenderError> {
+    let s = match (format, uppercase) {
+        ("hyphenated", false) => format!("{:x}", uuid.hyphenated()),
+        ("hyphenated", true) => format!("{:X}", uuid.hyphenated()),
+        ("simple", false) => format!("{:x}", uuid.simple()),
+        ("simple", true) => format!("{:X}", uuid.simple()),
+        ("urn", false) => format!("{:x}", uuid.urn()),
+        ("urn", true) => format!("{:X}", uuid.urn()),
+        ("braced", false) => format!("{:x}", uuid.braced()),
+        ("braced", true) => format!("{:X}", uuid.braced()),
+        _ => {
+            return Err(crate::to_other_error(format!(
+                "uuid format '{}' unknown (expected: hyphenated, simple, urn, braced)",
+                format
+            )))
+        }
+    };
+    Ok(s)
 }
 
-fn uuid_new_v7_fct(
-    _: &Helper,
-    _: &Handlebars,
-    _: &Context,
-    _: &mut RenderContext,
-    out: &mut dyn Output,
-) -> HelperResult {
-    out.write(Uuid::new_v4().to_string().as_str())?;
-    Ok(())
+fn parse_uuid(s: &str) -> Result<Uuid, handlebars::RenderError> {
+    Uuid::parse_str(s).map_err(crate::to_nested_error)
 }
 
-pub fn register(handlebars: &mut Handlebars) {
-    handlebars.register_helper("uuid_new_v4", Box::new(uuid_new_v4_fct));
-    handlebars.register_helper("uuid_new_v7", Box::new(uuid_new_v7_fct));
+/// namespace could be one of the predefined ("dns", "url", "oid", "x500") or any uuid
+fn parse_namespace(s: &str) -> Result<Uuid, handlebars::RenderError> {
+    match s.to_lowercase().as_str() {
+        "dns" => Ok(Uuid::NAMESPACE_DNS),
+        "url" => Ok(Uuid::NAMESPACE_URL),
+        "oid" => Ok(Uuid::NAMESPACE_OID),
+        "x500" => Ok(Uuid::NAMESPACE_X500),
+        _ => parse_uuid(s),
+    }
+}
+
+fn variant_name(variant: Variant) -> &'static str {
+    match variant {
+        Variant::NCS => "ncs",
+        Variant::RFC4122 => "rfc4122",
+        Variant::Microsoft => "microsoft",
+        Variant::Future => "future",
+        _ => "unknown",
+    }
+}
+
+fn uuid_to_json(uuid: Uuid) -> Json {
+    let timestamp = uuid.get_timestamp().map(|ts| {
+        let (secs, nanos) = ts.to_unix();
+        secs * 1000 + u64::from(nanos / 1_000_000)
+    });
+    json!({
+        "uuid": uuid.hyphenated().to_string(),
+        "version": uuid.get_version_num(),
+        "variant": variant_name(uuid.get_variant()),
+        "timestamp": timestamp,
+    })
+}
+
+pub(crate) const CATALOG: &[HelperInfo] = helpers_info! {
+    group: "uuid", feature: Some("uuid");
+    "uuid_new_v4"() [format: "string" = "hyphenated", uppercase: "boolean" = "false"] -> "string" {
+        r##"{{ len (uuid_new_v4) }}"## => "36"
+    }
+    "uuid_new_v7"() [format: "string" = "hyphenated", uppercase: "boolean" = "false"] -> "string" {
+        r##"{{ len (uuid_new_v7 format="simple") }}"## => "32"
+    }
+    "uuid_new_v5"(namespace: "string", name: "string") [format: "string" = "hyphenated", uppercase: "boolean" = "false"] -> "string" {
+        r##"{{ uuid_new_v5 "dns" "python.org" }}"## => "886313e1-3b8a-5372-9b90-0c9aee199e5d"
+    }
+    "uuid_new_v3"(namespace: "string", name: "string") [format: "string" = "hyphenated", uppercase: "boolean" = "false"] -> "string" {
+        r##"{{ uuid_new_v3 "dns" "python.org" }}"## => "6fa459ea-ee8a-3ca4-894e-db77e160355e"
+    }
+    "uuid_format"(uuid: "string") [format: "string" = "hyphenated", uppercase: "boolean" = "false"] -> "string" {
+        r##"{{ uuid_format "886313e1-3b8a-5372-9b90-0c9aee199e5d" format="simple" }}"## => "886313e13b8a53729b900c9aee199e5d"
+    }
+    "uuid_parse"(uuid: "string") -> "object" {
+        r##"{{ lookup (uuid_parse "017f22e2-79b0-7cc3-98c4-dc0c0c07398f") "timestamp" }}"## => "1645557742000"
+    }
+    "uuid_is_valid"(s: "string") -> "boolean" {
+        r##"{{ uuid_is_valid "foo" }}"## => "false"
+    }
+};
+
+pub fn register(handlebars: &mut impl HelperRegistry) {
+    {
+        handlebars_helper!(uuid_new_v4: |{format: str = "hyphenated", uppercase: bool = false}| format_uuid(Uuid::new_v4(), format, uppercase)?);
+        handlebars.register_helper("uuid_new_v4", Box::new(uuid_new_v4))
+    }
+    {
+        handlebars_helper!(uuid_new_v7: |{format: str = "hyphenated", uppercase: bool = false}| format_uuid(Uuid::now_v7(), format, uppercase)?);
+        handlebars.register_helper("uuid_new_v7", Box::new(uuid_new_v7))
+    }
+    {
+        handlebars_helper!(uuid_new_v5: |namespace: str, name: str, {format: str = "hyphenated", uppercase: bool = false}| {
+            format_uuid(Uuid::new_v5(&parse_namespace(namespace)?, name.as_bytes()), format, uppercase)?
+        });
+        handlebars.register_helper("uuid_new_v5", Box::new(uuid_new_v5))
+    }
+    {
+        handlebars_helper!(uuid_new_v3: |namespace: str, name: str, {format: str = "hyphenated", uppercase: bool = false}| {
+            format_uuid(Uuid::new_v3(&parse_namespace(namespace)?, name.as_bytes()), format, uppercase)?
+        });
+        handlebars.register_helper("uuid_new_v3", Box::new(uuid_new_v3))
+    }
+    {
+        handlebars_helper!(uuid_format: |v: str, {format: str = "hyphenated", uppercase: bool = false}| format_uuid(parse_uuid(v)?, format, uppercase)?);
+        handlebars.register_helper("uuid_format", Box::new(uuid_format))
+    }
+    {
+        handlebars_helper!(uuid_parse: |v: str| uuid_to_json(parse_uuid(v)?));
+        handlebars.register_helper("uuid_parse", Box::new(uuid_parse))
+    }
+    {
+        handlebars_helper!(uuid_is_valid: |v: str| Uuid::parse_str(v).is_ok());
+        handlebars.register_helper("uuid_is_valid", Box::new(uuid_is_valid))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::assert_renders;
+    use std::collections::HashMap;
     use std::error::Error;
 
     #[test]
-    fn test_regex_captures() -> Result<(), Box<dyn Error>> {
+    fn test_uuid_new() -> Result<(), Box<dyn Error>> {
         assert_renders![
             // (r##"{{ uuid_new_v4 }}"##, r##""##),
-            (r##"{{ len (uuid_new_v4) }}"##, r##"36"##),
             // (r##"{{ uuid_new_v7 }}"##, r##""##),
             (r##"{{ len( uuid_new_v7 )}}"##, r##"36"##),
+            (r##"{{ len (uuid_new_v4 format="simple") }}"##, r##"32"##),
+            (r##"{{ len (uuid_new_v7 format="urn") }}"##, r##"45"##),
+            (r##"{{ len (uuid_new_v7 format="braced") }}"##, r##"38"##),
+            (
+                r##"{{ lookup (uuid_parse (uuid_new_v4)) "version" }}"##,
+                r##"4"##
+            ),
+            (
+                r##"{{ lookup (uuid_parse (uuid_new_v7)) "version" }}"##,
+                r##"7"##
+            ),
         ]
     }
+
+    #[test]
+    fn test_uuid_new_v7_is_time_ordered() -> Result<(), Box<dyn Error>> {
+        let vs: HashMap<String, String> = HashMap::new();
+        let hbs = crate::new_hbs();
+        let ids = (0..100)
+            .map(|_| hbs.render_template("{{ uuid_new_v7 }}", &vs))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+        Ok(())
+    }
+
+    #[test]
+    fn test_uuid_new_v5_v3() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ uuid_new_v5 "6ba7b810-9dad-11d1-80b4-00c04fd430c8" "python.org" }}"##,
+                r##"886313e1-3b8a-5372-9b90-0c9aee199e5d"##
+            ),
+            (
+                r##"{{ uuid_new_v5 "dns" "python.org" format="simple" uppercase=true }}"##,
+                r##"886313E13B8A53729B900C9AEE199E5D"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_uuid_format() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ uuid_format "886313E1-3B8A-5372-9B90-0C9AEE199E5D" }}"##,
+                r##"886313e1-3b8a-5372-9b90-0c9aee199e5d"##
+            ),
+            (
+                r##"{{ uuid_format "886313e1-3b8a-5372-9b90-0c9aee199e5d" format="urn" uppercase=true }}"##,
+                r##"urn:uuid:886313E1-3B8A-5372-9B90-0C9AEE199E5D"##
+            ),
+            (
+                r##"{{ uuid_format "886313e13b8a53729b900c9aee199e5d" format="braced" }}"##,
+                r##"{886313e1-3b8a-5372-9b90-0c9aee199e5d}"##
+            ),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_uuid_parse() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ json_to_str (uuid_parse "886313e1-3b8a-5372-9b90-0c9aee199e5d") }}"##,
+                r##"{"timestamp":null,"uuid":"886313e1-3b8a-5372-9b90-0c9aee199e5d","variant":"rfc4122","version":5}"##
+            ),
+            (
+                r##"{{ json_to_str (uuid_parse "urn:uuid:017f22e2-79b0-7cc3-98c4-dc0c0c07398f") }}"##,
+                r##"{"timestamp":1645557742000,"uuid":"017f22e2-79b0-7cc3-98c4-dc0c0c07398f","variant":"rfc4122","version":7}"##
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_uuid_is_valid() -> Result<(), Box<dyn Error>> {
+        assert_renders![
+            (
+                r##"{{ uuid_is_valid "886313e1-3b8a-5372-9b90-0c9aee199e5d" }}"##,
+                r##"true"##
+            ),
+            (r##"{{ uuid_is_valid "886313e1" }}"##, r##"false"##),
+            (r##"{{ uuid_is_valid "" }}"##, r##"false"##),
+        ]
+    }
+
+    #[test]
+    fn test_uuid_invalid_input() {
+        let vs: HashMap<String, String> = HashMap::new();
+        let hbs = crate::new_hbs();
+        assert!(hbs
+            .render_template(r##"{{ uuid_parse "foo" }}"##, &vs)
+            .is_err());
+        assert!(hbs
+            .render_template(r##"{{ uuid_new_v5 "foo" "bar" }}"##, &vs)
+            .is_err());
+        assert!(hbs
+            .render_template(r##"{{ uuid_new_v4 format="foo" }}"##, &vs)
+            .is_err());
+    }
 }